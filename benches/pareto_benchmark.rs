@@ -0,0 +1,43 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use genetic::fitness::{pareto_ranks, pareto_ranks_naive};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+fn random_points(size: usize, num_objectives: usize, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    (0..size)
+        .map(|_| (0..num_objectives).map(|_| rng.gen()).collect())
+        .collect()
+}
+
+/// Points on the plane `sum(x) = 1`, so that they all form a single front.
+fn single_front(size: usize, num_objectives: usize, seed: u64) -> Vec<Vec<f64>> {
+    random_points(size, num_objectives, seed)
+        .into_iter()
+        .map(|point| {
+            let sum: f64 = point.iter().sum();
+            point.into_iter().map(|x| x / sum).collect()
+        })
+        .collect()
+}
+
+fn pareto_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pareto_ranks");
+    group.sample_size(10);
+
+    for size in [500, 2000, 5000] {
+        for (name, points) in [("random", random_points(size, 3, 0)), ("single_front", single_front(size, 3, 1))] {
+            group.bench_with_input(BenchmarkId::new(format!("fast/{}", name), size), &points, |b, points| {
+                b.iter(|| pareto_ranks(points).unwrap())
+            });
+            group.bench_with_input(BenchmarkId::new(format!("naive/{}", name), size), &points, |b, points| {
+                b.iter(|| pareto_ranks_naive(points))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, pareto_benchmark);
+criterion_main!(benches);
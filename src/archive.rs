@@ -0,0 +1,261 @@
+//! A bounded archive of non-dominated solutions, kept across generations.
+//!
+//! Reinsertion may drop non-dominated individuals that lose on crowding distance.
+//! [`ParetoArchive`] is an [`Observer`] that collects the non-dominated individuals of
+//! every sorted population (parents and children, before reinsertion), so the best
+//! trade-offs found during a run can be queried at its end.
+
+use crate::types::*;
+use crate::population::*;
+use crate::observer::*;
+use crate::fitness::{Direction, ParetoFitness, crowding_distances, dominates, orient_objectives};
+use crate::error::*;
+
+/// How the archive is kept within its capacity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchivePruning {
+    /// When full, repeatedly remove the member with the smallest crowding distance.
+    Crowding,
+    /// Keep at most one member per epsilon box (one epsilon per objective), the box being
+    /// `floor(objective / epsilon)`. Members whose box is dominated are removed. If the
+    /// archive is still over capacity, it is pruned by crowding distance.
+    EpsilonDominance(Vec<f64>),
+}
+
+/// A member of a [`ParetoArchive`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry<G> {
+    pub genome: G,
+    pub objectives: Vec<f64>,
+    /// The generation the individual was born in.
+    pub generation: u64,
+}
+
+pub struct ParetoArchive<G> {
+    capacity: usize,
+    pruning: ArchivePruning,
+    directions: Vec<Direction>,
+    entries: Vec<ArchiveEntry<G>>,
+}
+
+/// The epsilon box of oriented `objectives`.
+fn epsilon_box(epsilons: &[f64], objectives: &[f64]) -> Vec<f64> {
+    objectives.iter().zip(epsilons).map(|(value, epsilon)| (value / epsilon).floor()).collect()
+}
+
+impl<G: Genotype> ParetoArchive<G> {
+    /// An archive of at most `capacity` members, pruned by crowding distance.
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, pruning: ArchivePruning::Crowding, directions: Vec::new(), entries: Vec::new() }
+    }
+
+    pub fn with_pruning(mut self, pruning: ArchivePruning) -> Self {
+        self.pruning = pruning;
+        self
+    }
+
+    /// The directions of the objectives, which must match those of the fitness function.
+    /// Without directions, all objectives are maximized.
+    pub fn with_directions(mut self, directions: Vec<Direction>) -> Self {
+        self.directions = directions;
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The current non-dominated front.
+    pub fn front(&self) -> &[ArchiveEntry<G>] {
+        &self.entries
+    }
+
+    pub fn into_front(self) -> Vec<ArchiveEntry<G>> {
+        self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn check_objectives(&self, objectives: &[f64]) -> Result<()> {
+        let expected = self.entries
+            .first()
+            .map(|entry| entry.objectives.len())
+            .or_else(|| (!self.directions.is_empty()).then_some(self.directions.len()));
+        if expected.is_some_and(|expected| expected != objectives.len()) {
+            return Err(Error::Genetic("All archived solutions must have the same number of objectives".into()));
+        }
+        if let ArchivePruning::EpsilonDominance(epsilons) = &self.pruning {
+            if epsilons.len() != objectives.len() || epsilons.iter().any(|epsilon| *epsilon <= 0.0) {
+                return Err(Error::Genetic("Epsilon dominance needs one positive epsilon per objective".into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Offer a solution to the archive. Returns whether it is a member afterwards.
+    pub fn insert(&mut self, genome: G, objectives: Vec<f64>, generation: u64) -> Result<bool> {
+        self.check_objectives(&objectives)?;
+        let entry = ArchiveEntry { genome, objectives, generation };
+        let added = match &self.pruning {
+            ArchivePruning::Crowding => self.insert_pareto(entry),
+            ArchivePruning::EpsilonDominance(epsilons) => {
+                let epsilons = epsilons.clone();
+                self.insert_epsilon(&epsilons, entry)
+            },
+        };
+        Ok(self.prune(added.then(|| self.entries.len() - 1)))
+    }
+
+    fn insert_pareto(&mut self, entry: ArchiveEntry<G>) -> bool {
+        let candidate = orient_objectives(&self.directions, &entry.objectives);
+        let rejected = self.entries.iter().any(|member| {
+            let member = orient_objectives(&self.directions, &member.objectives);
+            member == candidate || dominates(&member, &candidate)
+        });
+        if rejected {
+            return false;
+        }
+
+        let directions = &self.directions;
+        self.entries.retain(|member| !dominates(&candidate, &orient_objectives(directions, &member.objectives)));
+        self.entries.push(entry);
+        true
+    }
+
+    fn insert_epsilon(&mut self, epsilons: &[f64], entry: ArchiveEntry<G>) -> bool {
+        let directions = &self.directions;
+        let candidate = orient_objectives(directions, &entry.objectives);
+        let candidate_box = epsilon_box(epsilons, &candidate);
+
+        for (i, member) in self.entries.iter().enumerate() {
+            let member_objectives = orient_objectives(directions, &member.objectives);
+            let member_box = epsilon_box(epsilons, &member_objectives);
+            if dominates(&member_box, &candidate_box) {
+                return false;
+            }
+            if member_box == candidate_box {
+                // Within the same box, keep the dominating solution, or else the one closer
+                // to the box's best corner.
+                let corner: Vec<f64> = candidate_box.iter().zip(epsilons).map(|(b, e)| (b + 1.0) * e).collect();
+                let distance = |point: &[f64]| point.iter().zip(corner.iter()).map(|(p, c)| (c - p).powi(2)).sum::<f64>();
+                let replace = dominates(&candidate, &member_objectives)
+                    || (!dominates(&member_objectives, &candidate) && distance(&candidate) < distance(&member_objectives));
+                if replace {
+                    self.entries.remove(i);
+                    self.entries.push(entry);
+                }
+                return replace;
+            }
+        }
+
+        self.entries.retain(|member| {
+            !dominates(&candidate_box, &epsilon_box(epsilons, &orient_objectives(directions, &member.objectives)))
+        });
+        self.entries.push(entry);
+        true
+    }
+
+    /// Remove the most crowded members until the archive fits its capacity.
+    /// Returns whether the member at index `newest` survived.
+    fn prune(&mut self, mut newest: Option<usize>) -> bool {
+        while self.entries.len() > self.capacity {
+            let objectives: Vec<Vec<f64>> = self.entries.iter().map(|entry| entry.objectives.clone()).collect();
+            let distances = crowding_distances(&objectives);
+            let most_crowded = distances
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(i, _)| i)
+                .unwrap();
+            self.entries.remove(most_crowded);
+            newest = newest.and_then(|newest| match newest.cmp(&most_crowded) {
+                std::cmp::Ordering::Less => Some(newest),
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Greater => Some(newest - 1),
+            });
+        }
+        newest.is_some()
+    }
+
+    /// Offer every individual of the first front of `population` to the archive.
+    pub fn update(&mut self, population: &SortedPopulation<G, ParetoFitness>) -> Result<()> {
+        for individual in population.individuals.iter() {
+            if let Some(fitness) = individual.fitness.as_ref().filter(|fitness| fitness.rank() == 0) {
+                self.insert(individual.genome.clone(), fitness.objectives().to_vec(), individual.generation)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<G: Genotype> Observer<G, ParetoFitness> for ParetoArchive<G> {
+    fn after_sort(&mut self, population: &SortedPopulation<G, ParetoFitness>) -> Result<()> {
+        self.update(population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn objectives<G: Genotype>(archive: &ParetoArchive<G>) -> Vec<Vec<f64>> {
+        let mut objectives: Vec<_> = archive.front().iter().map(|entry| entry.objectives.clone()).collect();
+        objectives.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        objectives
+    }
+
+    #[test]
+    fn test_archive_keeps_non_dominated() {
+        let mut archive = ParetoArchive::new(10);
+        assert!(archive.insert(vec![0], vec![1.0, 1.0], 0).unwrap());
+        assert!(archive.insert(vec![1], vec![2.0, 0.0], 0).unwrap());
+        assert!(!archive.insert(vec![2], vec![0.5, 0.5], 1).unwrap());
+        assert!(!archive.insert(vec![3], vec![1.0, 1.0], 1).unwrap());
+        assert!(archive.insert(vec![4], vec![1.5, 1.5], 2).unwrap());
+
+        assert_eq!(objectives(&archive), vec![vec![1.5, 1.5], vec![2.0, 0.0]]);
+        assert!(archive.insert(vec![5], vec![1.0], 3).is_err());
+    }
+
+    #[test]
+    fn test_archive_respects_directions() {
+        let mut archive = ParetoArchive::new(10).with_directions(vec![Direction::Minimize, Direction::Maximize]);
+        archive.insert(vec![0], vec![1.0, 1.0], 0).unwrap();
+        archive.insert(vec![1], vec![0.0, 1.0], 0).unwrap();
+        assert_eq!(objectives(&archive), vec![vec![0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_crowding_pruning_keeps_extremes() {
+        let mut archive = ParetoArchive::new(3);
+        for i in 0..=10 {
+            archive.insert(vec![i], vec![i as f64, 10.0 - i as f64], 0).unwrap();
+        }
+        let front = objectives(&archive);
+        assert_eq!(front.len(), 3);
+        assert_eq!(front[0], vec![0.0, 10.0]);
+        assert_eq!(front[2], vec![10.0, 0.0]);
+    }
+
+    #[test]
+    fn test_epsilon_pruning_keeps_one_per_box() {
+        let mut archive = ParetoArchive::new(100).with_pruning(ArchivePruning::EpsilonDominance(vec![1.0, 1.0]));
+        for i in 0..=20 {
+            let x = i as f64 / 2.0;
+            archive.insert(vec![i], vec![x, 10.0 - x], 0).unwrap();
+        }
+        // Boxes of width 1 along the line x + y = 10 hold one point each.
+        assert_eq!(archive.len(), 11);
+        assert!(archive.front().iter().all(|entry| entry.objectives[0].fract() == 0.0));
+    }
+}
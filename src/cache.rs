@@ -0,0 +1,350 @@
+//! Memoized evaluation of genomes.
+//!
+//! [`FitnessCache`] wraps an [`Incubator`] and a [`FitnessFunction`] and remembers the fitness of
+//! the most recently evaluated genomes, keyed by a hash of the genome. A genome seen before, in
+//! this or an earlier generation, is neither grown nor evaluated again. The same cache is used as
+//! both the incubator and the fitness function:
+//!
+//! ```
+//! use genetic::prelude::*;
+//!
+//! let fitness_function = SimpleFitness::new(|p: &Vec<i64>| Ok(p.iter().sum::<i64>())).use_existing_fitness();
+//! let cache = FitnessCache::new(IdentityIncubator::default(), fitness_function, 10_000);
+//! let builder = GeneticAlgorithmBuilder::default()
+//!     .with_incubator(cache.clone())
+//!     .with_fitness_function(cache.clone());
+//! ```
+//!
+//! Only the genomes missing from the cache are passed on to the wrapped fitness function, so its
+//! values must not depend on the rest of the population, unlike
+//! [`ParetoFitnessFunction`](crate::fitness::ParetoFitnessFunction). Cached fitnesses are never
+//! recalculated, whatever the fitness behavior of the wrapped function.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::types::*;
+use crate::error::*;
+
+/// Hit and miss counters of a [`FitnessCache`].
+///
+/// Individuals that are evaluated with a fitness already, like the survivors of the previous
+/// generation, count as neither unless they had to be grown again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Genomes without a fitness whose fitness was found in the cache, or bred twice in the
+    /// same batch.
+    pub hits: u64,
+    /// Genomes that were grown and evaluated.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// The share of counted genomes that were hits, 0 before any was counted.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+/// The phenotype grown by a [`FitnessCache`]: either the cached fitness, or the grown phenotype
+/// of a genome to evaluate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CachedPhenotype<P, F> {
+    Cached(F),
+    Grown { key: u64, phenotype: P },
+    /// A genome grown earlier in the same batch.
+    Duplicate(u64),
+}
+
+impl<P: Clone, F: Clone> Phenotype for CachedPhenotype<P, F> {}
+
+/// Least recently used fitnesses by key.
+struct Lru<F> {
+    capacity: usize,
+    entries: HashMap<u64, (F, u64)>,
+    recency: BTreeMap<u64, u64>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl<F: Clone> Lru<F> {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, entries: HashMap::new(), recency: BTreeMap::new(), clock: 0, stats: CacheStats::default() }
+    }
+
+    fn touch(&mut self, key: u64) -> u64 {
+        self.recency.insert(self.clock, key);
+        self.clock += 1;
+        self.clock - 1
+    }
+
+    fn get(&mut self, key: u64) -> Option<F> {
+        let used = self.entries.get(&key)?.1;
+        self.recency.remove(&used);
+        let used = self.touch(key);
+        let entry = self.entries.get_mut(&key)?;
+        entry.1 = used;
+        Some(entry.0.clone())
+    }
+
+    fn insert(&mut self, key: u64, fitness: F) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some((_, used)) = self.entries.remove(&key) {
+            self.recency.remove(&used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        let used = self.touch(key);
+        self.entries.insert(key, (fitness, used));
+    }
+}
+
+type GenomeKey<G> = Arc<dyn Fn(&G) -> u64 + Send + Sync>;
+
+/// A bounded LRU cache of fitnesses in front of an incubator and a fitness function.
+///
+/// Clones share the cache. Genomes are identified by a 64 bit hash only, so two genomes with
+/// colliding hashes share a fitness.
+pub struct FitnessCache<I, FF>
+    where
+        I: Incubator,
+        FF: FitnessFunction,
+{
+    incubator: Arc<I>,
+    fitness_function: Arc<FF>,
+    key: GenomeKey<I::Genotype>,
+    lru: Arc<Mutex<Lru<FF::Fitness>>>,
+}
+
+impl<I, FF> FitnessCache<I, FF>
+    where
+        I: Incubator,
+        I::Genotype: Hash,
+        FF: FitnessFunction<Phenotype = I::Phenotype>,
+{
+    /// Cache the fitness of up to `capacity` genomes, keyed by their `Hash`.
+    pub fn new(incubator: I, fitness_function: FF, capacity: usize) -> Self {
+        Self::with_key(incubator, fitness_function, capacity, |genome: &I::Genotype| {
+            let mut hasher = DefaultHasher::new();
+            genome.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+}
+
+impl<I, FF> FitnessCache<I, FF>
+    where
+        I: Incubator,
+        FF: FitnessFunction<Phenotype = I::Phenotype>,
+{
+    /// Key genomes with `key`, e.g. for genomes of floats, which are not `Hash`.
+    pub fn with_key<K>(incubator: I, fitness_function: FF, capacity: usize, key: K) -> Self
+        where K: Fn(&I::Genotype) -> u64 + Send + Sync + 'static
+    {
+        Self {
+            incubator: Arc::new(incubator),
+            fitness_function: Arc::new(fitness_function),
+            key: Arc::new(key),
+            lru: Arc::new(Mutex::new(Lru::new(capacity))),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lru
+            .lock()
+            .map(|lru| lru.stats)
+            .unwrap_or_default()
+    }
+
+    /// The number of cached fitnesses.
+    pub fn len(&self) -> usize {
+        self.lru
+            .lock()
+            .map(|lru| lru.entries.len())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every cached fitness, keeping the counters.
+    pub fn clear(&self) {
+        if let Ok(mut lru) = self.lru.lock() {
+            lru.entries.clear();
+            lru.recency.clear();
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Lru<FF::Fitness>>> {
+        self.lru
+            .lock()
+            .map_err(|_| Error::Genetic("Fitness cache is poisoned".into()))
+    }
+}
+
+impl<I, FF> Clone for FitnessCache<I, FF>
+    where
+        I: Incubator,
+        FF: FitnessFunction,
+{
+    fn clone(&self) -> Self {
+        Self {
+            incubator: self.incubator.clone(),
+            fitness_function: self.fitness_function.clone(),
+            key: self.key.clone(),
+            lru: self.lru.clone(),
+        }
+    }
+}
+
+impl<I, FF> Incubator for FitnessCache<I, FF>
+    where
+        I: Incubator,
+        FF: FitnessFunction<Phenotype = I::Phenotype>,
+{
+    type Genotype = I::Genotype;
+    type Phenotype = CachedPhenotype<I::Phenotype, FF::Fitness>;
+
+    fn grow(&self, genome: &Self::Genotype) -> Result<Self::Phenotype> {
+        Ok(self.grow_all(&[genome])?.remove(0))
+    }
+
+    /// Look every genome up in the cache and grow the missing ones, each distinct genome once.
+    fn grow_all(&self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Phenotype>> {
+        let mut phenotypes = Vec::with_capacity(genomes.len());
+        let mut missing = Vec::new();
+        let mut seen = HashSet::new();
+        {
+            let mut lru = self.lock()?;
+            for genome in genomes {
+                let key = (self.key)(genome);
+                if let Some(fitness) = lru.get(key) {
+                    phenotypes.push(Some(CachedPhenotype::Cached(fitness)));
+                } else if !seen.insert(key) {
+                    phenotypes.push(Some(CachedPhenotype::Duplicate(key)));
+                } else {
+                    phenotypes.push(None);
+                    missing.push((key, *genome));
+                }
+            }
+        }
+
+        let missing_genomes: Vec<_> = missing.iter().map(|(_, genome)| *genome).collect();
+        let mut grown = missing
+            .iter()
+            .zip(self.incubator.grow_all(&missing_genomes)?)
+            .map(|((key, _), phenotype)| CachedPhenotype::Grown { key: *key, phenotype });
+        phenotypes
+            .into_iter()
+            .map(|phenotype| phenotype.or_else(|| grown.next()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::Genetic("Incubator grew fewer phenotypes than genomes".into()))
+    }
+}
+
+impl<I, FF> FitnessFunction for FitnessCache<I, FF>
+    where
+        I: Incubator,
+        FF: FitnessFunction<Phenotype = I::Phenotype>,
+{
+    type Phenotype = CachedPhenotype<I::Phenotype, FF::Fitness>;
+    type Fitness = FF::Fitness;
+
+    fn evaluate(&self, phenotypes_with_fitnesses: &[(&Self::Phenotype, Option<&Self::Fitness>)]) -> Result<Vec<Self::Fitness>> {
+        let (keys, grown): (Vec<u64>, Vec<_>) = phenotypes_with_fitnesses
+            .iter()
+            .filter_map(|(phenotype, fitness)| match phenotype {
+                CachedPhenotype::Grown { key, phenotype } => Some((*key, (phenotype, *fitness))),
+                _ => None,
+            })
+            .unzip();
+        let fitnesses = self.fitness_function.evaluate(&grown)?;
+
+        let evaluated: HashMap<u64, FF::Fitness> = keys.into_iter().zip(fitnesses).collect();
+        {
+            let mut lru = self.lock()?;
+            for (key, fitness) in evaluated.iter() {
+                lru.insert(*key, fitness.clone());
+            }
+            for (phenotype, fitness) in phenotypes_with_fitnesses {
+                match phenotype {
+                    CachedPhenotype::Grown { .. } => lru.stats.misses += 1,
+                    _ if fitness.is_none() => lru.stats.hits += 1,
+                    _ => {},
+                }
+            }
+        }
+
+        phenotypes_with_fitnesses
+            .iter()
+            .map(|(phenotype, _)| match phenotype {
+                CachedPhenotype::Cached(fitness) => Ok(fitness.clone()),
+                CachedPhenotype::Grown { key, .. } | CachedPhenotype::Duplicate(key) => evaluated
+                    .get(key)
+                    .cloned()
+                    .ok_or_else(|| Error::Genetic("Duplicate genome evaluated without its original".into())),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::prelude::*;
+    use super::*;
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut lru = Lru::new(2);
+        lru.insert(1, 10);
+        lru.insert(2, 20);
+        assert_eq!(lru.get(1), Some(10));
+        lru.insert(3, 30);
+        assert_eq!(lru.get(2), None);
+        assert_eq!(lru.get(1), Some(10));
+        assert_eq!(lru.get(3), Some(30));
+        lru.insert(1, 11);
+        assert_eq!(lru.entries.len(), 2);
+        assert_eq!(lru.get(1), Some(11));
+    }
+
+    #[test]
+    fn test_sort_skips_cached_genomes() {
+        let evaluations = Arc::new(AtomicUsize::new(0));
+        let counter = evaluations.clone();
+        let fitness_function = SimpleFitness::new(move |p: &Vec<i64>| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(p.iter().sum::<i64>())
+        }).recalculate_fitness();
+        let cache = FitnessCache::new(IdentityIncubator::default(), fitness_function, 100);
+
+        let population = UnsortedPopulation::default()
+            .add_children(vec![vec![1, 2], vec![3], vec![1, 2], vec![0]])
+            .sort(&cache, &cache)
+            .unwrap();
+        assert_eq!(population.fitnesses().map(|fitness| *fitness.unwrap()).collect::<Vec<_>>(), vec![3, 3, 3, 0]);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 3);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 3 });
+
+        // Across generations, even fitnesses the wrapped function would recalculate are reused.
+        let population = population
+            .add_children(vec![vec![3], vec![4]])
+            .sort(&cache, &cache)
+            .unwrap();
+        assert_eq!(population.best().genome, vec![4]);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 4);
+        // The survivors arrive with their fitness, and do not count as hits.
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 4 });
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.stats().hit_rate(), 2.0 / 6.0);
+    }
+}
@@ -0,0 +1,221 @@
+//! Checkpointing and resuming of evolutionary runs.
+//!
+//! A checkpoint holds the population, the [`RunProgress`], the state of every
+//! operator of the algorithm and the next id to give an individual, so ids are not
+//! reused after resuming. Operators opt in by implementing [`Stateful`].
+//! When all operators use seeded, serializable RNGs (e.g. `rand_chacha::ChaCha8Rng`
+//! with the `serde1` feature), a resumed run continues exactly like the
+//! uninterrupted one would have.
+//!
+//! Termination criteria are not part of the checkpoint, so stateful criteria such
+//! as [`Stagnation`](crate::termination::Stagnation) start counting afresh on resume.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::population::*;
+use crate::termination::*;
+use crate::observer::*;
+use crate::dedup::ChildFilter;
+use crate::ga::*;
+use crate::error::*;
+
+/// An operator whose internal state (usually an RNG) can be saved and restored.
+pub trait Stateful {
+    type State: Serialize + DeserializeOwned;
+
+    fn state(&self) -> Self::State;
+    fn restore(&mut self, state: Self::State);
+}
+
+impl Stateful for () {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+impl<I, F, S, C, M, R, O, U> Stateful for GeneticAlgorithm<I, F, S, C, M, R, O, U>
+    where
+        S: Stateful,
+        C: Stateful,
+        M: Stateful,
+        R: Stateful,
+{
+    /// The operator states, and the next id to give an individual.
+    type State = (S::State, C::State, M::State, R::State, u64);
+
+    fn state(&self) -> Self::State {
+        (self.select.state(), self.crossover.state(), self.mutate.state(), self.reinsert.state(), self.next_id)
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.select.restore(state.0);
+        self.crossover.restore(state.1);
+        self.mutate.restore(state.2);
+        self.reinsert.restore(state.3);
+        self.next_id = state.4;
+    }
+}
+
+/// Writes a checkpoint to `path` every `interval` generations.
+#[derive(Debug, Clone)]
+pub struct Checkpointer {
+    pub path: PathBuf,
+    pub interval: u64,
+}
+
+impl Checkpointer {
+    pub fn new<P: Into<PathBuf>>(path: P, interval: u64) -> Self {
+        Self { path: path.into(), interval }
+    }
+
+    /// Atomically write `value` to the checkpoint path.
+    ///
+    /// The data is written to a temporary file next to the checkpoint, which then
+    /// replaces the previous checkpoint, so a crash never leaves a partial file behind.
+    pub fn save<T: Serialize>(&self, value: &T) -> Result<()> {
+        let tmp_path = tmp_path(&self.path);
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            bincode::serialize_into(&mut writer, value)?;
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub fn load<T: DeserializeOwned>(&self) -> Result<T> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        Ok(bincode::deserialize_from(reader)?)
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithm<I, F, S, C, M, R, O, U>
+    where
+        I: Incubator,
+        F: FitnessFunction<Phenotype = I::Phenotype>,
+        S: SelectOperator<F::Fitness> + Stateful,
+        C: CrossoverOperator<Genotype = I::Genotype> + Stateful,
+        M: MutateOperator<Genotype = I::Genotype> + Stateful,
+        R: ReinsertOperator<I::Genotype, F::Fitness> + Stateful,
+        O: Observer<I::Genotype, F::Fitness>,
+        U: ChildFilter<I::Genotype>,
+        I::Genotype: Serialize + DeserializeOwned,
+        F::Fitness: Serialize + DeserializeOwned,
+{
+    /// Like [`run`](GeneticAlgorithm::run), but writes a checkpoint every `checkpointer.interval` generations.
+    pub fn run_with_checkpoints<T>(&mut self, population: SortedPopulation<I::Genotype, F::Fitness>, criterion: T, checkpointer: &Checkpointer) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>
+    {
+        let progress = RunProgress {
+            evaluations: population.get_num_children() as u64,
+            ..RunProgress::default()
+        };
+        self.run_checkpointed(population, progress, criterion, checkpointer)
+    }
+
+    /// Restore the population, progress and operator states from the last checkpoint and continue the run.
+    pub fn resume<T>(&mut self, criterion: T, checkpointer: &Checkpointer) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>
+    {
+        let (population, progress, state) = checkpointer.load()?;
+        self.restore(state);
+        self.run_checkpointed(population, progress, criterion, checkpointer)
+    }
+
+    fn run_checkpointed<T>(&mut self, population: SortedPopulation<I::Genotype, F::Fitness>, progress: RunProgress, criterion: T, checkpointer: &Checkpointer) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>
+    {
+        let interval = checkpointer.interval.max(1);
+        self.run_from(population, progress, criterion, |ga, population, progress| {
+            if progress.generations % interval == 0 {
+                checkpointer.save(&(population, progress, ga.state()))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use super::*;
+
+    struct NoMutation;
+
+    impl MutateOperator for NoMutation {
+        type Genotype = Vec<i64>;
+
+        fn mutate(&mut self, _genome: &mut Self::Genotype) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Stateful for NoMutation {
+        type State = ();
+
+        fn state(&self) -> Self::State {}
+        fn restore(&mut self, _state: Self::State) {}
+    }
+
+    fn test_genomes() -> Vec<Vec<i64>> {
+        (0..30).map(|i| vec![i % 7, (i * 13) % 11 - 5, 3 - i % 4]).collect()
+    }
+
+    fn genomes_fitnesses_and_ids(population: &SortedPopulation<Vec<i64>, i64>) -> Vec<(Vec<i64>, Option<i64>, Option<u64>)> {
+        population.individuals
+            .iter()
+            .map(|individual| (individual.genome.clone(), individual.fitness, individual.id))
+            .collect()
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let builder = |seed| GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(|p: &Vec<i64>| Ok(p[0] * p[1] - p[2])).use_existing_fitness())
+            .with_select(TournamentSelectionBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(seed)).build())
+            .with_crossover(ExhausiveCrossover::default())
+            .with_mutate(NoMutation)
+            .with_reinsert(ElitistReinserter);
+
+        let path = std::env::temp_dir().join(format!("genetic-checkpoint-{}.bin", std::process::id()));
+        let checkpointer = Checkpointer::new(&path, 5);
+
+        let uninterrupted = builder(7);
+        let population = uninterrupted.create_population(test_genomes()).unwrap();
+        let mut uninterrupted = uninterrupted.build();
+        let expected = uninterrupted.run(population, MaxGenerations(10)).unwrap();
+
+        let interrupted = builder(7);
+        let population = interrupted.create_population(test_genomes()).unwrap();
+        interrupted.build().run_with_checkpoints(population, MaxGenerations(7), &checkpointer).unwrap();
+
+        let mut resumed_algorithm = builder(123).build();
+        let resumed = resumed_algorithm.resume(MaxGenerations(10), &checkpointer).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.progress.generations, 10);
+        assert_eq!(resumed.progress.evaluations, expected.progress.evaluations);
+        assert_eq!(genomes_fitnesses_and_ids(&resumed.population), genomes_fitnesses_and_ids(&expected.population));
+        assert_eq!(resumed_algorithm.next_id, uninterrupted.next_id);
+    }
+}
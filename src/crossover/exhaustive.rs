@@ -7,6 +7,7 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 use crate::error::*;
 use crate::types::*;
+use crate::checkpoint::Stateful;
 use serde::Deserialize;
 
 
@@ -35,6 +36,13 @@ impl<G> CrossoverOperator for ExhausiveCrossover<G>
     }
 }
 
+impl<G> Stateful for ExhausiveCrossover<G> {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
 /// Perform an exhaustive crossover between two parent genotypes.
 ///
 /// Given two parent genotypes of equal length, this function generates all
@@ -1,5 +1,11 @@
 pub mod unique_genes;
 pub mod exhaustive;
+pub mod point;
+pub mod permutation;
+pub mod real;
 
 pub use unique_genes::*;
-pub use exhaustive::*;
\ No newline at end of file
+pub use exhaustive::*;
+pub use point::*;
+pub use permutation::*;
+pub use real::*;
\ No newline at end of file
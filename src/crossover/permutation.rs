@@ -0,0 +1,670 @@
+//! Crossovers for permutation genomes.
+//!
+//! Like [`UniquenessPreservativeCrossover`](super::UniquenessPreservativeCrossover), genes are
+//! identified by their `AsRef<usize>` value, but these operators also keep positional or
+//! adjacency information of the parents. Both parents must be permutations of the same genes.
+//!
+//! The `*_crossover` functions implement the operators on gene ids and produce a single child;
+//! swapping the parents gives the complementary child. A gene of a child is cloned from the parent
+//! it has the same position in, preferring the child's first parent, or from the first parent if
+//! it moved, so genes that carry more than their id keep the data of the parent they came from.
+
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::check_probability;
+
+/// Validated parents of a permutation crossover.
+struct PermutationParents<'a, G> {
+    ids1: Vec<usize>,
+    ids2: Vec<usize>,
+    genomes: [&'a [G]; 2],
+    positions: [HashMap<usize, usize>; 2],
+}
+
+impl<'a, G> PermutationParents<'a, G>
+    where G: AsRef<usize> + Clone
+{
+    fn new(name: &str, genomes: &[&'a Vec<G>]) -> Result<Self> {
+        if genomes.len() != 2 {
+            return Err(Error::Genetic(format!("{} crossover only works with 2 parents", name)));
+        }
+
+        let ids1: Vec<usize> = genomes[0].iter().map(|gene| *gene.as_ref()).collect();
+        let ids2: Vec<usize> = genomes[1].iter().map(|gene| *gene.as_ref()).collect();
+        let positions = [positions(&ids1), positions(&ids2)];
+
+        let is_permutation = ids1.len() == ids2.len()
+            && positions[0].len() == ids1.len()
+            && positions[1].len() == ids2.len()
+            && ids2.iter().all(|id| positions[0].contains_key(id));
+        if !is_permutation {
+            return Err(Error::Genetic("Parents must be permutations of the same genes".into()));
+        }
+
+        Ok(Self { ids1, ids2, genomes: [genomes[0], genomes[1]], positions })
+    }
+
+    fn ids(&self, parent: usize) -> &[usize] {
+        if parent == 0 { &self.ids1 } else { &self.ids2 }
+    }
+
+    /// The genes of a child bred with parent `first` (0 or 1) as its first parent.
+    fn to_genome(&self, ids: Vec<usize>, first: usize) -> Vec<G> {
+        let second = 1 - first;
+        ids.into_iter()
+            .enumerate()
+            .map(|(position, id)| {
+                let parent = if self.ids(first)[position] != id && self.ids(second)[position] == id { second } else { first };
+                self.genomes[parent][self.positions[parent][&id]].clone()
+            })
+            .collect()
+    }
+
+    fn random_segment<R: Rng>(&self, rng: &mut R) -> (usize, usize) {
+        let len = self.ids1.len();
+        let a = rng.gen_range(0..=len);
+        let b = rng.gen_range(0..=len);
+        (a.min(b), a.max(b))
+    }
+}
+
+fn positions(ids: &[usize]) -> HashMap<usize, usize> {
+    ids.iter().enumerate().map(|(position, id)| (*id, position)).collect()
+}
+
+/// Partially mapped crossover (PMX).
+///
+/// The child inherits `parent1[start..end]`. The genes of `parent2` in that segment are placed by
+/// following the mapping between the parents' segments, and the rest is copied from `parent2`.
+pub fn partially_mapped_crossover(parent1: &[usize], parent2: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let mut child: Vec<Option<usize>> = vec![None; parent1.len()];
+    child[start..end]
+        .iter_mut()
+        .zip(parent1[start..end].iter())
+        .for_each(|(slot, id)| *slot = Some(*id));
+
+    let in_segment: HashSet<usize> = parent1[start..end].iter().copied().collect();
+    let positions2 = positions(parent2);
+
+    for (position, &id) in parent2.iter().enumerate().take(end).skip(start) {
+        if in_segment.contains(&id) {
+            continue;
+        }
+        let mut target = position;
+        while (start..end).contains(&target) {
+            target = positions2[&parent1[target]];
+        }
+        child[target] = Some(id);
+    }
+
+    child
+        .into_iter()
+        .zip(parent2.iter())
+        .map(|(slot, id)| slot.unwrap_or(*id))
+        .collect()
+}
+
+/// Order crossover (OX1).
+///
+/// The child inherits `parent1[start..end]`, the remaining positions are filled starting after the
+/// segment with the genes of `parent2` in the order they appear after the segment, wrapping around.
+pub fn order_crossover(parent1: &[usize], parent2: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let len = parent1.len();
+    let mut child: Vec<Option<usize>> = vec![None; len];
+    let mut used = HashSet::new();
+    for position in start..end {
+        child[position] = Some(parent1[position]);
+        used.insert(parent1[position]);
+    }
+
+    let mut donors = (0..len)
+        .map(|offset| parent2[(end + offset) % len])
+        .filter(|id| !used.contains(id));
+    for offset in 0..len {
+        let position = (end + offset) % len;
+        if child[position].is_none() {
+            child[position] = donors.next();
+        }
+    }
+
+    child.into_iter().flatten().collect()
+}
+
+/// Cycle crossover (CX).
+///
+/// Positions are partitioned into the cycles of the mapping between the parents. The child takes the
+/// genes of `parent1` in the first, third, ... cycle and those of `parent2` in the others, so every
+/// gene keeps the position it had in one of the parents.
+pub fn cycle_crossover(parent1: &[usize], parent2: &[usize]) -> Vec<usize> {
+    let positions1 = positions(parent1);
+    let mut child: Vec<Option<usize>> = vec![None; parent1.len()];
+    let mut from_parent1 = true;
+
+    for start in 0..parent1.len() {
+        if child[start].is_some() {
+            continue;
+        }
+        let mut position = start;
+        loop {
+            child[position] = Some(if from_parent1 { parent1[position] } else { parent2[position] });
+            position = positions1[&parent2[position]];
+            if position == start {
+                break;
+            }
+        }
+        from_parent1 = !from_parent1;
+    }
+
+    child.into_iter().flatten().collect()
+}
+
+/// Position-based crossover.
+///
+/// The child takes the genes of `parent1` at the positions where `keep` is true, and the remaining
+/// genes in the order they appear in `parent2`.
+pub fn position_based_crossover(parent1: &[usize], parent2: &[usize], keep: &[bool]) -> Vec<usize> {
+    let kept: HashSet<usize> = parent1
+        .iter()
+        .zip(keep.iter())
+        .filter(|(_, keep)| **keep)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut donors = parent2.iter().filter(|id| !kept.contains(id));
+
+    parent1
+        .iter()
+        .zip(keep.iter())
+        .map(|(id, keep)| if *keep { *id } else { *donors.next().unwrap() })
+        .collect()
+}
+
+/// Edge recombination crossover (ERX).
+///
+/// Builds the union of the parents' (cyclic) adjacencies and walks it starting at `parent1[0]`,
+/// always moving to the unvisited neighbour with the fewest remaining neighbours. Ties, and dead
+/// ends where no neighbour is left, are resolved randomly.
+pub fn edge_recombination_crossover<R: Rng>(parent1: &[usize], parent2: &[usize], rng: &mut R) -> Vec<usize> {
+    let len = parent1.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut edges: HashMap<usize, HashSet<usize>> = HashMap::with_capacity(len);
+    for parent in [parent1, parent2] {
+        for position in 0..len {
+            let neighbours = edges.entry(parent[position]).or_default();
+            if len > 1 {
+                neighbours.insert(parent[(position + len - 1) % len]);
+                neighbours.insert(parent[(position + 1) % len]);
+            }
+        }
+    }
+
+    let mut unvisited: Vec<usize> = parent1.to_vec();
+    let mut child = Vec::with_capacity(len);
+    let mut current = parent1[0];
+
+    loop {
+        child.push(current);
+        unvisited.retain(|id| *id != current);
+        if unvisited.is_empty() {
+            break;
+        }
+
+        let neighbours = edges.remove(&current).unwrap_or_default();
+        for other in neighbours.iter() {
+            if let Some(edges) = edges.get_mut(other) {
+                edges.remove(&current);
+            }
+        }
+
+        let mut candidates: Vec<usize> = neighbours.into_iter().collect();
+        candidates.sort_unstable();
+        let fewest = candidates.iter().map(|id| edges[id].len()).min();
+        candidates.retain(|id| Some(edges[id].len()) == fewest);
+
+        current = *candidates
+            .choose(rng)
+            .unwrap_or_else(|| unvisited.choose(rng).unwrap());
+    }
+
+    child
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PartiallyMappedCrossoverBuilder<R> {
+    rng: R,
+}
+
+impl Default for PartiallyMappedCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rng: (),
+        }
+    }
+}
+
+impl<R> PartiallyMappedCrossoverBuilder<R> {
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> PartiallyMappedCrossoverBuilder<RNG> {
+        PartiallyMappedCrossoverBuilder {
+            rng
+        }
+    }
+}
+
+impl<R> PartiallyMappedCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> PartiallyMappedCrossover<R, G> {
+        PartiallyMappedCrossover {
+            rng: self.rng,
+            gene: PhantomData
+        }
+    }
+}
+
+/// Partially mapped crossover (PMX) on a random segment, see [`partially_mapped_crossover`].
+pub struct PartiallyMappedCrossover<R, G> {
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for PartiallyMappedCrossover<R, G>
+    where R: Rng, G: AsRef<usize> + Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let parents = PermutationParents::new("Partially mapped", genomes)?;
+        let (start, end) = parents.random_segment(&mut self.rng);
+        Ok(vec![
+            parents.to_genome(partially_mapped_crossover(&parents.ids1, &parents.ids2, start, end), 0),
+            parents.to_genome(partially_mapped_crossover(&parents.ids2, &parents.ids1, start, end), 1),
+        ])
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct OrderCrossoverBuilder<R> {
+    rng: R,
+}
+
+impl Default for OrderCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rng: (),
+        }
+    }
+}
+
+impl<R> OrderCrossoverBuilder<R> {
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> OrderCrossoverBuilder<RNG> {
+        OrderCrossoverBuilder {
+            rng
+        }
+    }
+}
+
+impl<R> OrderCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> OrderCrossover<R, G> {
+        OrderCrossover {
+            rng: self.rng,
+            gene: PhantomData
+        }
+    }
+}
+
+/// Order crossover (OX1) on a random segment, see [`order_crossover`].
+pub struct OrderCrossover<R, G> {
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for OrderCrossover<R, G>
+    where R: Rng, G: AsRef<usize> + Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let parents = PermutationParents::new("Order", genomes)?;
+        let (start, end) = parents.random_segment(&mut self.rng);
+        Ok(vec![
+            parents.to_genome(order_crossover(&parents.ids1, &parents.ids2, start, end), 0),
+            parents.to_genome(order_crossover(&parents.ids2, &parents.ids1, start, end), 1),
+        ])
+    }
+}
+
+/// Cycle crossover (CX), see [`cycle_crossover`]. It is deterministic and needs no RNG.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CycleCrossover<G> { _phantom: PhantomData<G> }
+
+impl<G> Default for CycleCrossover<G>
+{
+    fn default() -> Self {
+        Self { _phantom: PhantomData }
+    }
+}
+
+impl<G> CrossoverOperator for CycleCrossover<G>
+    where G: AsRef<usize> + Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let parents = PermutationParents::new("Cycle", genomes)?;
+        Ok(vec![
+            parents.to_genome(cycle_crossover(&parents.ids1, &parents.ids2), 0),
+            parents.to_genome(cycle_crossover(&parents.ids2, &parents.ids1), 1),
+        ])
+    }
+}
+
+impl<G> Stateful for CycleCrossover<G> {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PositionBasedCrossoverBuilder<R> {
+    pub position_probability: f64,
+    rng: R,
+}
+
+impl Default for PositionBasedCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            position_probability: 0.5,
+            rng: (),
+        }
+    }
+}
+
+impl<R> PositionBasedCrossoverBuilder<R> {
+    pub fn with_position_probability(mut self, position_probability: f64) -> Self {
+        self.position_probability = position_probability;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> PositionBasedCrossoverBuilder<RNG> {
+        PositionBasedCrossoverBuilder {
+            position_probability: self.position_probability,
+            rng
+        }
+    }
+}
+
+impl<R> PositionBasedCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> Result<PositionBasedCrossover<R, G>> {
+        check_probability("Position probability", self.position_probability)?;
+        Ok(PositionBasedCrossover {
+            position_probability: self.position_probability,
+            rng: self.rng,
+            gene: PhantomData
+        })
+    }
+}
+
+/// Position-based crossover keeping each position with `position_probability`, see [`position_based_crossover`].
+pub struct PositionBasedCrossover<R, G> {
+    pub position_probability: f64,
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for PositionBasedCrossover<R, G>
+    where R: Rng, G: AsRef<usize> + Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let parents = PermutationParents::new("Position-based", genomes)?;
+        let keep: Vec<bool> = (0..parents.ids1.len())
+            .map(|_| self.rng.gen_bool(self.position_probability))
+            .collect();
+        Ok(vec![
+            parents.to_genome(position_based_crossover(&parents.ids1, &parents.ids2, &keep), 0),
+            parents.to_genome(position_based_crossover(&parents.ids2, &parents.ids1, &keep), 1),
+        ])
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct EdgeRecombinationCrossoverBuilder<R> {
+    rng: R,
+}
+
+impl Default for EdgeRecombinationCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rng: (),
+        }
+    }
+}
+
+impl<R> EdgeRecombinationCrossoverBuilder<R> {
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> EdgeRecombinationCrossoverBuilder<RNG> {
+        EdgeRecombinationCrossoverBuilder {
+            rng
+        }
+    }
+}
+
+impl<R> EdgeRecombinationCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> EdgeRecombinationCrossover<R, G> {
+        EdgeRecombinationCrossover {
+            rng: self.rng,
+            gene: PhantomData
+        }
+    }
+}
+
+/// Edge recombination crossover (ERX), see [`edge_recombination_crossover`].
+///
+/// Produces one child starting from each parent.
+pub struct EdgeRecombinationCrossover<R, G> {
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for EdgeRecombinationCrossover<R, G>
+    where R: Rng, G: AsRef<usize> + Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let parents = PermutationParents::new("Edge recombination", genomes)?;
+        Ok(vec![
+            parents.to_genome(edge_recombination_crossover(&parents.ids1, &parents.ids2, &mut self.rng), 0),
+            parents.to_genome(edge_recombination_crossover(&parents.ids2, &parents.ids1, &mut self.rng), 1),
+        ])
+    }
+}
+
+impl<R, G> Stateful for PartiallyMappedCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, G> Stateful for OrderCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, G> Stateful for PositionBasedCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, G> Stateful for EdgeRecombinationCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use proptest::prelude::*;
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestGene(usize);
+
+    impl AsRef<usize> for TestGene {
+        fn as_ref(&self) -> &usize {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_position_based_crossover_rejects_invalid_position_probability() {
+        for probability in [-0.1, 1.5, f64::NAN] {
+            let crossover = PositionBasedCrossoverBuilder::default()
+                .with_position_probability(probability)
+                .with_rng(StdRng::seed_from_u64(0))
+                .build::<TestGene>();
+            assert!(crossover.is_err(), "{}", probability);
+        }
+    }
+
+    #[test]
+    fn test_partially_mapped_crossover() {
+        let parent1 = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let parent2 = vec![9, 3, 7, 8, 2, 6, 5, 1, 4];
+        assert_eq!(partially_mapped_crossover(&parent1, &parent2, 3, 7), vec![9, 3, 2, 4, 5, 6, 7, 1, 8]);
+    }
+
+    #[test]
+    fn test_order_crossover() {
+        let parent1 = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let parent2 = vec![9, 3, 7, 8, 2, 6, 5, 1, 4];
+        assert_eq!(order_crossover(&parent1, &parent2, 3, 7), vec![3, 8, 2, 4, 5, 6, 7, 1, 9]);
+    }
+
+    #[test]
+    fn test_cycle_crossover() {
+        let parent1 = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let parent2 = vec![9, 3, 7, 8, 2, 6, 5, 1, 4];
+        assert_eq!(cycle_crossover(&parent1, &parent2), vec![1, 3, 7, 4, 2, 6, 5, 8, 9]);
+    }
+
+    #[test]
+    fn test_position_based_crossover() {
+        let parent1 = vec![1, 2, 3, 4, 5];
+        let parent2 = vec![5, 4, 3, 2, 1];
+        let keep = vec![true, false, true, false, false];
+        assert_eq!(position_based_crossover(&parent1, &parent2, &keep), vec![1, 5, 3, 4, 2]);
+    }
+
+    #[test]
+    fn test_rejects_parents_that_are_not_permutations_of_each_other() {
+        let mut crossover = CycleCrossover::default();
+        let parent1 = vec![TestGene(0), TestGene(1), TestGene(2)];
+        let parent2 = vec![TestGene(0), TestGene(1), TestGene(3)];
+        assert!(crossover.crossover(&[&parent1, &parent2]).is_err());
+    }
+
+    #[test]
+    fn test_children_keep_the_genes_of_the_parent_they_inherit_from() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct TaggedGene(usize, char);
+
+        impl AsRef<usize> for TaggedGene {
+            fn as_ref(&self) -> &usize {
+                &self.0
+            }
+        }
+
+        let parent1: Vec<TaggedGene> = [1, 2, 3, 4].into_iter().map(|id| TaggedGene(id, 'a')).collect();
+        let parent2: Vec<TaggedGene> = [2, 1, 4, 3].into_iter().map(|id| TaggedGene(id, 'b')).collect();
+        let children = CycleCrossover::default().crossover(&[&parent1, &parent2]).unwrap();
+        let tags: Vec<String> = children.iter().map(|child| child.iter().map(|gene| gene.1).collect()).collect();
+        assert_eq!(tags, vec!["aabb", "bbaa"]);
+    }
+
+    fn is_permutation_of(child: &[TestGene], parent: &[TestGene]) -> bool {
+        let mut child: Vec<usize> = child.iter().map(|gene| gene.0).collect();
+        let mut parent: Vec<usize> = parent.iter().map(|gene| gene.0).collect();
+        child.sort_unstable();
+        parent.sort_unstable();
+        child == parent
+    }
+
+    proptest! {
+        #[test]
+        fn test_children_are_permutations(
+            genes in proptest::collection::hash_set(0usize..1000, 0..40),
+            seed in 0u64..1000
+        ) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let parent1: Vec<TestGene> = genes.into_iter().map(TestGene).collect();
+            let mut parent2 = parent1.clone();
+            parent2.shuffle(&mut rng);
+
+            let mut pmx = PartiallyMappedCrossoverBuilder::default().with_rng(rng.clone()).build();
+            let mut ox = OrderCrossoverBuilder::default().with_rng(rng.clone()).build();
+            let mut cx = CycleCrossover::default();
+            let mut position_based = PositionBasedCrossoverBuilder::default().with_rng(rng.clone()).build().unwrap();
+            let mut erx = EdgeRecombinationCrossoverBuilder::default().with_rng(rng).build();
+
+            let operators: [&mut dyn CrossoverOperator<Genotype = Vec<TestGene>>; 5] = [&mut pmx, &mut ox, &mut cx, &mut position_based, &mut erx];
+            for operator in operators {
+                let children = operator.crossover(&[&parent1, &parent2]).unwrap();
+                prop_assert_eq!(children.len(), 2);
+                for child in children.iter() {
+                    prop_assert!(is_permutation_of(child, &parent1));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Classic crossovers for fixed-length genomes.
+//!
+//! All of them take two parents of equal length and produce two complementary children.
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+use rand::seq::index;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+
+/// Check that there are exactly two parents of equal length, and return them.
+pub(crate) fn two_parents<'a, G>(name: &str, genomes: &[&'a Vec<G>]) -> Result<(&'a Vec<G>, &'a Vec<G>)> {
+    if genomes.len() != 2 {
+        return Err(Error::Genetic(format!("{} crossover only works with 2 parents", name)));
+    }
+    if genomes[0].len() != genomes[1].len() {
+        return Err(Error::Genetic("Parent genotypes must have the same length".into()));
+    }
+    Ok((genomes[0], genomes[1]))
+}
+
+/// Check that `probability`, the `what` of a crossover, is within `[0, 1]`.
+pub(crate) fn check_probability(what: &str, probability: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(Error::Genetic(format!("{} {} is not a probability", what, probability)));
+    }
+    Ok(())
+}
+
+/// Produce two children by exchanging the segments of the parents between the cut points.
+///
+/// `cut_points` must be sorted. The first child starts with genes of `parent1` and
+/// switches parent at every cut point, the second child is its complement.
+pub fn k_point_crossover<G: Clone>(parent1: &[G], parent2: &[G], cut_points: &[usize]) -> Vec<Vec<G>> {
+    let mut child1 = Vec::with_capacity(parent1.len());
+    let mut child2 = Vec::with_capacity(parent2.len());
+    let mut cut_points = cut_points.iter().peekable();
+    let mut swapped = false;
+
+    for locus in 0..parent1.len() {
+        while cut_points.next_if(|&&point| point <= locus).is_some() {
+            swapped = !swapped;
+        }
+        let (gene1, gene2) = if swapped {
+            (&parent2[locus], &parent1[locus])
+        } else {
+            (&parent1[locus], &parent2[locus])
+        };
+        child1.push(gene1.clone());
+        child2.push(gene2.clone());
+    }
+
+    vec![child1, child2]
+}
+
+/// Up to `k` distinct sorted cut points in `1..len`.
+fn random_cut_points<R: Rng>(rng: &mut R, len: usize, k: usize) -> Vec<usize> {
+    if len < 2 {
+        return Vec::new();
+    }
+    let mut points: Vec<usize> = index::sample(rng, len - 1, k.min(len - 1))
+        .into_iter()
+        .map(|point| point + 1)
+        .collect();
+    points.sort_unstable();
+    points
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct OnePointCrossoverBuilder<R> {
+    rng: R,
+}
+
+impl Default for OnePointCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rng: (),
+        }
+    }
+}
+
+impl<R> OnePointCrossoverBuilder<R> {
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> OnePointCrossoverBuilder<RNG> {
+        OnePointCrossoverBuilder {
+            rng
+        }
+    }
+}
+
+impl<R> OnePointCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> OnePointCrossover<R, G> {
+        OnePointCrossover {
+            rng: self.rng,
+            gene: PhantomData
+        }
+    }
+}
+
+/// Exchanges the tails of the parents after a random cut point.
+pub struct OnePointCrossover<R, G> {
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for OnePointCrossover<R, G>
+    where R: Rng, G: Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("One-point", genomes)?;
+        let cut_points = random_cut_points(&mut self.rng, parent1.len(), 1);
+        Ok(k_point_crossover(parent1, parent2, &cut_points))
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct TwoPointCrossoverBuilder<R> {
+    rng: R,
+}
+
+impl Default for TwoPointCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rng: (),
+        }
+    }
+}
+
+impl<R> TwoPointCrossoverBuilder<R> {
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> TwoPointCrossoverBuilder<RNG> {
+        TwoPointCrossoverBuilder {
+            rng
+        }
+    }
+}
+
+impl<R> TwoPointCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> TwoPointCrossover<R, G> {
+        TwoPointCrossover {
+            rng: self.rng,
+            gene: PhantomData
+        }
+    }
+}
+
+/// Exchanges the segment of the parents between two random cut points.
+pub struct TwoPointCrossover<R, G> {
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for TwoPointCrossover<R, G>
+    where R: Rng, G: Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Two-point", genomes)?;
+        let cut_points = random_cut_points(&mut self.rng, parent1.len(), 2);
+        Ok(k_point_crossover(parent1, parent2, &cut_points))
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct KPointCrossoverBuilder<R> {
+    pub num_points: usize,
+    rng: R,
+}
+
+impl Default for KPointCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_points: 3,
+            rng: (),
+        }
+    }
+}
+
+impl<R> KPointCrossoverBuilder<R> {
+    pub fn with_num_points(mut self, num_points: usize) -> Self {
+        self.num_points = num_points;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> KPointCrossoverBuilder<RNG> {
+        KPointCrossoverBuilder {
+            num_points: self.num_points,
+            rng
+        }
+    }
+}
+
+impl<R> KPointCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> KPointCrossover<R, G> {
+        KPointCrossover {
+            num_points: self.num_points,
+            rng: self.rng,
+            gene: PhantomData
+        }
+    }
+}
+
+/// Alternates between the parents at `num_points` random cut points.
+pub struct KPointCrossover<R, G> {
+    pub num_points: usize,
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for KPointCrossover<R, G>
+    where R: Rng, G: Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("K-point", genomes)?;
+        let cut_points = random_cut_points(&mut self.rng, parent1.len(), self.num_points);
+        Ok(k_point_crossover(parent1, parent2, &cut_points))
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct UniformCrossoverBuilder<R> {
+    pub swap_probability: f64,
+    rng: R,
+}
+
+impl Default for UniformCrossoverBuilder<()> {
+    fn default() -> Self {
+        Self {
+            swap_probability: 0.5,
+            rng: (),
+        }
+    }
+}
+
+impl<R> UniformCrossoverBuilder<R> {
+    pub fn with_swap_probability(mut self, swap_probability: f64) -> Self {
+        self.swap_probability = swap_probability;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> UniformCrossoverBuilder<RNG> {
+        UniformCrossoverBuilder {
+            swap_probability: self.swap_probability,
+            rng
+        }
+    }
+}
+
+impl<R> UniformCrossoverBuilder<R> where R: Rng {
+    pub fn build<G>(self) -> Result<UniformCrossover<R, G>> {
+        check_probability("Swap probability", self.swap_probability)?;
+        Ok(UniformCrossover {
+            swap_probability: self.swap_probability,
+            rng: self.rng,
+            gene: PhantomData
+        })
+    }
+}
+
+/// Exchanges every gene of the parents independently with `swap_probability`.
+pub struct UniformCrossover<R, G> {
+    pub swap_probability: f64,
+    rng: R,
+    gene: PhantomData<G>,
+}
+
+impl<R, G> CrossoverOperator for UniformCrossover<R, G>
+    where R: Rng, G: Clone + Send + Sync
+{
+    type Genotype = Vec<G>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Uniform", genomes)?;
+        let mut child1 = parent1.clone();
+        let mut child2 = parent2.clone();
+        for locus in 0..child1.len() {
+            if self.rng.gen_bool(self.swap_probability) {
+                std::mem::swap(&mut child1[locus], &mut child2[locus]);
+            }
+        }
+        Ok(vec![child1, child2])
+    }
+}
+
+impl<R, G> Stateful for OnePointCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, G> Stateful for TwoPointCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, G> Stateful for KPointCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, G> Stateful for UniformCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use proptest::prelude::*;
+    use super::*;
+
+    #[test]
+    fn test_k_point_crossover() {
+        let parent1 = vec![1, 1, 1, 1, 1, 1];
+        let parent2 = vec![2, 2, 2, 2, 2, 2];
+
+        let children = k_point_crossover(&parent1, &parent2, &[2, 5]);
+        assert_eq!(children, vec![vec![1, 1, 2, 2, 2, 1], vec![2, 2, 1, 1, 1, 2]]);
+    }
+
+    #[test]
+    fn test_crossover_requires_two_parents_of_equal_length() {
+        let mut crossover = OnePointCrossoverBuilder::default().with_rng(StdRng::seed_from_u64(0)).build();
+        assert!(crossover.crossover(&[&vec![1, 2], &vec![3]]).is_err());
+        assert!(crossover.crossover(&[&vec![1, 2]]).is_err());
+    }
+
+    #[test]
+    fn test_uniform_crossover_rejects_invalid_swap_probability() {
+        for probability in [-0.1, 1.5, f64::NAN] {
+            let crossover = UniformCrossoverBuilder::default()
+                .with_swap_probability(probability)
+                .with_rng(StdRng::seed_from_u64(0))
+                .build::<i32>();
+            assert!(crossover.is_err(), "{}", probability);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_children_are_complementary(len in 0usize..40, num_points in 1usize..6, seed in 0u64..1000) {
+            let parent1: Vec<i32> = (0..len as i32).collect();
+            let parent2: Vec<i32> = (0..len as i32).map(|x| -x - 1).collect();
+            let parents = [&parent1, &parent2];
+            let rng = StdRng::seed_from_u64(seed);
+
+            let mut one_point = OnePointCrossoverBuilder::default().with_rng(rng.clone()).build();
+            let mut two_point = TwoPointCrossoverBuilder::default().with_rng(rng.clone()).build();
+            let mut k_point = KPointCrossoverBuilder::default().with_num_points(num_points).with_rng(rng.clone()).build();
+            let mut uniform = UniformCrossoverBuilder::default().with_rng(rng).build().unwrap();
+
+            let operators: [&mut dyn CrossoverOperator<Genotype = Vec<i32>>; 4] = [&mut one_point, &mut two_point, &mut k_point, &mut uniform];
+            for operator in operators {
+                let children = operator.crossover(&parents).unwrap();
+                prop_assert_eq!(children.len(), 2);
+                for locus in 0..len {
+                    let mut genes = [children[0][locus], children[1][locus]];
+                    genes.sort();
+                    prop_assert_eq!(genes, [parent2[locus], parent1[locus]]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,628 @@
+//! Crossovers for real-valued genomes.
+//!
+//! All of them work on `Vec<T>` where `T` is a [`RealGene`] (`f32` or `f64`), take two
+//! parents of equal length and clamp the children's genes to the bounds if any are given.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::genotypes::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::two_parents;
+
+/// Blend `parent1` and `parent2` as `weight * parent1 + (1 - weight) * parent2` from `start` on,
+/// copying `parent1` before it.
+fn blend<T: RealGene>(bounds: &Option<Bounds<T>>, parent1: &[T], parent2: &[T], weight: f64, start: usize) -> Vec<T> {
+    parent1
+        .iter()
+        .zip(parent2.iter())
+        .enumerate()
+        .map(|(locus, (x, y))| if locus < start {
+            *x
+        } else {
+            clamp_gene(bounds, locus, weight * x.as_f64() + (1.0 - weight) * y.as_f64())
+        })
+        .collect()
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SimulatedBinaryCrossoverBuilder<R, T> {
+    pub distribution_index: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for SimulatedBinaryCrossoverBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            distribution_index: 20.0,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> SimulatedBinaryCrossoverBuilder<R, T> {
+    pub fn with_distribution_index(mut self, distribution_index: f64) -> Self {
+        self.distribution_index = distribution_index;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> SimulatedBinaryCrossoverBuilder<RNG, T> {
+        SimulatedBinaryCrossoverBuilder {
+            distribution_index: self.distribution_index,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> SimulatedBinaryCrossoverBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<SimulatedBinaryCrossover<R, T>> {
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(SimulatedBinaryCrossover {
+            distribution_index: self.distribution_index,
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Simulated binary crossover (SBX).
+///
+/// Larger distribution indices produce children closer to their parents.
+pub struct SimulatedBinaryCrossover<R: Rng, T> {
+    pub distribution_index: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> CrossoverOperator for SimulatedBinaryCrossover<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Simulated binary", genomes)?;
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(parent1.len())?;
+        }
+        let exponent = 1.0 / (self.distribution_index + 1.0);
+        let mut child1 = Vec::with_capacity(parent1.len());
+        let mut child2 = Vec::with_capacity(parent2.len());
+
+        for (locus, (x, y)) in parent1.iter().zip(parent2.iter()).enumerate() {
+            let (x, y) = (x.as_f64(), y.as_f64());
+            let u: f64 = self.rng.gen();
+            let beta = if u <= 0.5 {
+                (2.0 * u).powf(exponent)
+            } else {
+                (1.0 / (2.0 * (1.0 - u))).powf(exponent)
+            };
+            child1.push(clamp_gene(&self.bounds, locus, 0.5 * ((1.0 + beta) * x + (1.0 - beta) * y)));
+            child2.push(clamp_gene(&self.bounds, locus, 0.5 * ((1.0 - beta) * x + (1.0 + beta) * y)));
+        }
+
+        Ok(vec![child1, child2])
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BlendCrossoverBuilder<R, T> {
+    pub alpha: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for BlendCrossoverBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            alpha: 0.5,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> BlendCrossoverBuilder<R, T> {
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> BlendCrossoverBuilder<RNG, T> {
+        BlendCrossoverBuilder {
+            alpha: self.alpha,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> BlendCrossoverBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<BlendCrossover<R, T>> {
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(BlendCrossover {
+            alpha: self.alpha,
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Blend crossover (BLX-α).
+///
+/// Every gene of the children is drawn uniformly from the interval spanned by the parents' genes,
+/// extended by `alpha` times its length on both sides.
+pub struct BlendCrossover<R: Rng, T> {
+    pub alpha: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> CrossoverOperator for BlendCrossover<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Blend", genomes)?;
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(parent1.len())?;
+        }
+        let mut children = vec![Vec::with_capacity(parent1.len()), Vec::with_capacity(parent1.len())];
+
+        for (locus, (x, y)) in parent1.iter().zip(parent2.iter()).enumerate() {
+            let (x, y) = (x.as_f64(), y.as_f64());
+            let extent = self.alpha * (x - y).abs();
+            let (low, high) = (x.min(y) - extent, x.max(y) + extent);
+            for child in children.iter_mut() {
+                let value = if high > low { self.rng.gen_range(low..=high) } else { low };
+                child.push(clamp_gene(&self.bounds, locus, value));
+            }
+        }
+
+        Ok(children)
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct WholeArithmeticCrossoverBuilder<R, T> {
+    pub weight: Option<f64>,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for WholeArithmeticCrossoverBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            weight: None,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> WholeArithmeticCrossoverBuilder<R, T> {
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> WholeArithmeticCrossoverBuilder<RNG, T> {
+        WholeArithmeticCrossoverBuilder {
+            weight: self.weight,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> WholeArithmeticCrossoverBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<WholeArithmeticCrossover<R, T>> {
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(WholeArithmeticCrossover {
+            weight: self.weight,
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Whole arithmetic crossover.
+///
+/// The children are the weighted averages `w * parent1 + (1 - w) * parent2` and
+/// `(1 - w) * parent1 + w * parent2`. Without a fixed `weight`, a new one is drawn
+/// uniformly from `[0, 1]` for every crossover.
+pub struct WholeArithmeticCrossover<R: Rng, T> {
+    pub weight: Option<f64>,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> CrossoverOperator for WholeArithmeticCrossover<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Whole arithmetic", genomes)?;
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(parent1.len())?;
+        }
+        let weight = self.weight.unwrap_or_else(|| self.rng.gen());
+        Ok(vec![
+            blend(&self.bounds, parent1, parent2, weight, 0),
+            blend(&self.bounds, parent2, parent1, weight, 0),
+        ])
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SimpleArithmeticCrossoverBuilder<R, T> {
+    pub weight: Option<f64>,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for SimpleArithmeticCrossoverBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            weight: None,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> SimpleArithmeticCrossoverBuilder<R, T> {
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> SimpleArithmeticCrossoverBuilder<RNG, T> {
+        SimpleArithmeticCrossoverBuilder {
+            weight: self.weight,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> SimpleArithmeticCrossoverBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<SimpleArithmeticCrossover<R, T>> {
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(SimpleArithmeticCrossover {
+            weight: self.weight,
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Simple arithmetic crossover.
+///
+/// Like [`WholeArithmeticCrossover`], but only the genes after a random cut point are averaged,
+/// the ones before it are copied from the respective parent.
+pub struct SimpleArithmeticCrossover<R: Rng, T> {
+    pub weight: Option<f64>,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> CrossoverOperator for SimpleArithmeticCrossover<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Simple arithmetic", genomes)?;
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(parent1.len())?;
+        }
+        let weight = self.weight.unwrap_or_else(|| self.rng.gen());
+        let start = self.rng.gen_range(0..=parent1.len());
+        Ok(vec![
+            blend(&self.bounds, parent1, parent2, weight, start),
+            blend(&self.bounds, parent2, parent1, weight, start),
+        ])
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct HeuristicCrossoverBuilder<R, T> {
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for HeuristicCrossoverBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> HeuristicCrossoverBuilder<R, T> {
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> HeuristicCrossoverBuilder<RNG, T> {
+        HeuristicCrossoverBuilder {
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> HeuristicCrossoverBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<HeuristicCrossover<R, T>> {
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(HeuristicCrossover {
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Heuristic crossover.
+///
+/// Produces a single child `parent1 + r * (parent1 - parent2)` with `r` drawn uniformly from `[0, 1]`,
+/// i.e. it extrapolates beyond the first parent, away from the second one. The first parent must
+/// be the better one, which holds for the groups of every [`SelectOperator`].
+pub struct HeuristicCrossover<R: Rng, T> {
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> CrossoverOperator for HeuristicCrossover<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>> {
+        let (parent1, parent2) = two_parents("Heuristic", genomes)?;
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(parent1.len())?;
+        }
+        let r: f64 = self.rng.gen();
+        Ok(vec![blend(&self.bounds, parent1, parent2, 1.0 + r, 0)])
+    }
+}
+
+impl<R, T> Stateful for SimulatedBinaryCrossover<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, T> Stateful for BlendCrossover<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, T> Stateful for WholeArithmeticCrossover<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, T> Stateful for SimpleArithmeticCrossover<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, T> Stateful for HeuristicCrossover<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::*;
+
+    #[test]
+    fn test_real_crossovers_respect_bounds() {
+        let bounds = Bounds::PerGene(vec![(0.0, 1.0), (-1.0, 0.0), (10.0, 20.0)]);
+        let parent1 = vec![0.0, -1.0, 19.0];
+        let parent2 = vec![1.0, -0.5, 11.0];
+
+        let mut sbx = SimulatedBinaryCrossoverBuilder::default()
+            .with_distribution_index(1.0)
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(0))
+            .build().unwrap();
+        let mut blend = BlendCrossoverBuilder::default()
+            .with_alpha(2.0)
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(1))
+            .build().unwrap();
+        let mut whole = WholeArithmeticCrossoverBuilder::default()
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(2))
+            .build().unwrap();
+        let mut simple = SimpleArithmeticCrossoverBuilder::default()
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(3))
+            .build().unwrap();
+        let mut heuristic = HeuristicCrossoverBuilder::default()
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(4))
+            .build().unwrap();
+
+        let operators: [&mut dyn CrossoverOperator<Genotype = Vec<f64>>; 5] = [&mut sbx, &mut blend, &mut whole, &mut simple, &mut heuristic];
+        for operator in operators {
+            for _ in 0..100 {
+                for child in operator.crossover(&[&parent1, &parent2]).unwrap() {
+                    for (locus, gene) in child.iter().enumerate() {
+                        let (low, high) = bounds.get(locus);
+                        assert!(*gene >= low && *gene <= high);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_real_crossovers_reject_inverted_bounds() {
+        let bounds = Bounds::Uniform(1.0, 0.0);
+        let rng = || StdRng::seed_from_u64(0);
+        assert!(SimulatedBinaryCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().is_err());
+        assert!(BlendCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().is_err());
+        assert!(WholeArithmeticCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().is_err());
+        assert!(SimpleArithmeticCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().is_err());
+        assert!(HeuristicCrossoverBuilder::default().with_bounds(bounds).with_rng(rng()).build().is_err());
+    }
+
+    #[test]
+    fn test_real_crossovers_reject_too_few_bounds() {
+        let bounds = Bounds::PerGene(vec![(0.0, 1.0)]);
+        let rng = || StdRng::seed_from_u64(0);
+        let mut sbx = SimulatedBinaryCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().unwrap();
+        let mut blend = BlendCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().unwrap();
+        let mut whole = WholeArithmeticCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().unwrap();
+        let mut simple = SimpleArithmeticCrossoverBuilder::default().with_bounds(bounds.clone()).with_rng(rng()).build().unwrap();
+        let mut heuristic = HeuristicCrossoverBuilder::default().with_bounds(bounds).with_rng(rng()).build().unwrap();
+
+        let operators: [&mut dyn CrossoverOperator<Genotype = Vec<f64>>; 5] = [&mut sbx, &mut blend, &mut whole, &mut simple, &mut heuristic];
+        for operator in operators {
+            assert!(operator.crossover(&[&vec![0.5, 0.5], &vec![0.0, 1.0]]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_whole_arithmetic_crossover() {
+        let mut crossover = WholeArithmeticCrossoverBuilder::default()
+            .with_weight(0.25)
+            .with_rng(StdRng::seed_from_u64(0))
+            .build().unwrap();
+        let children = crossover.crossover(&[&vec![0.0f32, 4.0], &vec![4.0, 8.0]]).unwrap();
+        assert_eq!(children, vec![vec![3.0, 7.0], vec![1.0, 5.0]]);
+    }
+
+    #[test]
+    fn test_heuristic_crossover_moves_away_from_second_parent() {
+        let mut crossover = HeuristicCrossoverBuilder::default()
+            .with_rng(StdRng::seed_from_u64(0))
+            .build().unwrap();
+        for _ in 0..100 {
+            let children = crossover.crossover(&[&vec![1.0, 1.0], &vec![0.0, 2.0]]).unwrap();
+            assert_eq!(children.len(), 1);
+            assert!(children[0][0] >= 1.0 && children[0][1] <= 1.0);
+        }
+    }
+
+    #[test]
+    fn test_sbx_preserves_mean() {
+        let mut crossover = SimulatedBinaryCrossoverBuilder::default()
+            .with_rng(StdRng::seed_from_u64(0))
+            .build().unwrap();
+        let children = crossover.crossover(&[&vec![1.0f64, -3.0], &vec![2.0, 5.0]]).unwrap();
+        for locus in 0..2 {
+            let mean = (children[0][locus] + children[1][locus]) / 2.0;
+            assert!((mean - [1.5, 1.0][locus]).abs() < 1e-9);
+        }
+    }
+}
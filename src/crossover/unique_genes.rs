@@ -1,11 +1,13 @@
 use std::marker::PhantomData;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 
 use rand::Rng;
 use rand::seq::IteratorRandom;
 
 use crate::types::*;
 use crate::error::*;
+use crate::checkpoint::Stateful;
 
 #[derive(Deserialize)]
 #[serde(default)]
@@ -52,7 +54,7 @@ impl<R> UniquenessPreservativeCrossoverBuilder<R>
 }
 
 pub struct UniquenessPreservativeCrossover<R, G> 
-    where R: Rng, G: Clone + Send + Sync
+    where R: Rng
 {
     pub num_children: usize,
     pub rng: R,
@@ -94,6 +96,20 @@ impl<R, G> CrossoverOperator for UniquenessPreservativeCrossover<R, G>
     }
 }
 
+impl<R, G> Stateful for UniquenessPreservativeCrossover<R, G>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
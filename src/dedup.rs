@@ -0,0 +1,315 @@
+//! Keeping duplicate genomes out of a population.
+//!
+//! A [`Deduplicate`] policy considers two genomes duplicates when they are equal, or closer
+//! than a threshold by some [`Distance`]. It is applied to the children with
+//! [`Population::add_unique_children`](crate::population::Population::add_unique_children)
+//! or, as a [`ChildFilter`], by the genetic algorithm, and to reinsertion by wrapping a
+//! reinserter in a [`UniqueReinserter`]. With a [`GenomeInitializer`], duplicates are replaced
+//! by fresh genomes instead of being rejected.
+
+use std::collections::HashMap;
+
+use crate::types::*;
+use crate::individual::*;
+use crate::population::*;
+use crate::fitness::Distance;
+use crate::checkpoint::Stateful;
+use crate::error::*;
+
+/// Creates new genomes, e.g. at random.
+pub trait GenomeInitializer<G> {
+    fn initialize(&mut self) -> Result<G>;
+}
+
+impl<G, T> GenomeInitializer<G> for T
+    where T: FnMut() -> Result<G>
+{
+    fn initialize(&mut self) -> Result<G> {
+        self()
+    }
+}
+
+/// 0 between equal genomes, infinite otherwise.
+pub struct Identical;
+
+impl<G: PartialEq> Distance<G> for Identical {
+    fn distance(&self, a: &G, b: &G) -> f64 {
+        if a == b { 0.0 } else { f64::INFINITY }
+    }
+}
+
+type BoxedInitializer<G> = Box<dyn GenomeInitializer<G> + Send>;
+
+/// When two genomes are duplicates, and what replaces a duplicate.
+pub struct Deduplicate<G, D = Identical> {
+    pub distance: D,
+    /// Genomes at most this far apart are duplicates.
+    pub threshold: f64,
+    /// How many fresh genomes to try before giving up on replacing a duplicate.
+    pub max_attempts: usize,
+    initializer: Option<BoxedInitializer<G>>,
+}
+
+impl<G: PartialEq> Deduplicate<G> {
+    /// Equal genomes are duplicates.
+    pub fn exact() -> Self {
+        Self::within(Identical, 0.0)
+    }
+}
+
+impl<G, D: Distance<G>> Deduplicate<G, D> {
+    /// Genomes at most `threshold` apart are duplicates.
+    pub fn within(distance: D, threshold: f64) -> Self {
+        Self { distance, threshold, max_attempts: 10, initializer: None }
+    }
+
+    /// Replace duplicates with genomes of `initializer` rather than rejecting them.
+    pub fn with_initializer<I>(mut self, initializer: I) -> Self
+        where I: GenomeInitializer<G> + Send + 'static
+    {
+        self.initializer = Some(Box::new(initializer));
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn is_duplicate(&self, a: &G, b: &G) -> bool {
+        self.distance.distance(a, b) <= self.threshold
+    }
+
+    fn is_duplicate_of<'a, T>(&self, genome: &G, others: T) -> bool
+        where
+            G: 'a,
+            T: IntoIterator<Item = &'a G>,
+    {
+        others.into_iter().any(|other| self.is_duplicate(genome, other))
+    }
+
+    /// A fresh genome that duplicates none of `others`, if there is an initializer and it
+    /// produced one within `max_attempts`.
+    fn fresh<'a, T>(&mut self, others: T) -> Result<Option<G>>
+        where
+            G: 'a,
+            T: IntoIterator<Item = &'a G> + Clone,
+    {
+        let initializer = match self.initializer.as_mut() {
+            Some(initializer) => initializer,
+            None => return Ok(None),
+        };
+        for _ in 0..self.max_attempts {
+            let genome = initializer.initialize()?;
+            if !others.clone().into_iter().any(|other| self.distance.distance(&genome, other) <= self.threshold) {
+                return Ok(Some(genome));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The `candidates` that duplicate neither `existing` nor an earlier candidate, in order.
+    /// Duplicates are replaced with fresh genomes, or dropped.
+    pub fn unique(&mut self, existing: &[&G], candidates: Vec<G>) -> Result<Vec<G>> {
+        self.unique_by(existing, candidates, |genome| genome, |genome, _| genome)
+    }
+
+    /// Like [`unique`](Self::unique), for candidates that carry a genome. `wrap` turns a fresh
+    /// genome into the candidate that replaces a duplicate.
+    fn unique_by<T>(&mut self, existing: &[&G], candidates: Vec<T>, genome: impl Fn(&T) -> &G, wrap: impl Fn(G, &T) -> T) -> Result<Vec<T>> {
+        let mut unique: Vec<T> = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            if !self.is_duplicate_of(genome(&candidate), existing.iter().copied().chain(unique.iter().map(&genome))) {
+                unique.push(candidate);
+            } else if let Some(fresh) = self.fresh(existing.iter().copied().chain(unique.iter().map(&genome)))? {
+                unique.push(wrap(fresh, &candidate));
+            }
+        }
+        Ok(unique)
+    }
+}
+
+/// Filters the children of a generation before they join the population, e.g. to keep
+/// duplicates out with a [`Deduplicate`]. `()` keeps every child.
+pub trait ChildFilter<G: Genotype> {
+    /// The `children` to add to a population of `existing` genomes.
+    fn filter<F: Fitness>(&mut self, existing: &[&G], children: Vec<Individual<G, F>>) -> Result<Vec<Individual<G, F>>>;
+}
+
+impl<G: Genotype> ChildFilter<G> for () {
+    fn filter<F: Fitness>(&mut self, _existing: &[&G], children: Vec<Individual<G, F>>) -> Result<Vec<Individual<G, F>>> {
+        Ok(children)
+    }
+}
+
+/// Keeps the [unique](Deduplicate::unique) children. A fresh genome that replaces a duplicate
+/// is a child without id, as it has no parents.
+impl<G: Genotype, D: Distance<G>> ChildFilter<G> for Deduplicate<G, D> {
+    fn filter<F: Fitness>(&mut self, existing: &[&G], children: Vec<Individual<G, F>>) -> Result<Vec<Individual<G, F>>> {
+        self.unique_by(
+            existing,
+            children,
+            |child| &child.genome,
+            |genome, child| Individual { generation: child.generation, genome, fitness: None, id: None },
+        )
+    }
+}
+
+/// Wraps a reinserter to keep duplicates out of the next generation.
+///
+/// Without an initializer, duplicates are removed before reinsertion, keeping the best of
+/// each, so the wrapped reinserter chooses survivors among unique individuals. With one, the
+/// wrapped reinserter chooses survivors as usual, then every duplicate survivor is replaced by
+/// an unevaluated fresh genome, counted as a child of the generation. A duplicate for which no
+/// fresh genome is found is kept.
+pub struct UniqueReinserter<R, G, D = Identical> {
+    pub reinsert: R,
+    pub deduplicate: Deduplicate<G, D>,
+}
+
+impl<R, G, D> UniqueReinserter<R, G, D> {
+    pub fn new(reinsert: R, deduplicate: Deduplicate<G, D>) -> Self {
+        Self { reinsert, deduplicate }
+    }
+}
+
+impl<R, G, D> UniqueReinserter<R, G, D>
+    where
+        G: Genotype,
+        D: Distance<G>,
+{
+    /// Deduplicate around `reinsert`, which calls the wrapped reinserter.
+    fn reinsert_unique<F, T>(&mut self, mut population: SortedPopulation<G, F>, reinsert: T) -> Result<UnsortedPopulation<G, F>>
+        where
+            F: Fitness,
+            T: FnOnce(&mut R, SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>>,
+    {
+        if self.deduplicate.initializer.is_none() {
+            let target = population.previous_generation_size();
+            let mut unique: Vec<Individual<G, F>> = Vec::with_capacity(population.individuals.len());
+            for individual in std::mem::take(&mut population.individuals) {
+                if !self.deduplicate.is_duplicate_of(&individual.genome, unique.iter().map(|kept| &kept.genome)) {
+                    unique.push(individual);
+                }
+            }
+            let num_children = population.num_children;
+            population.num_children = unique.len().saturating_sub(target);
+            population.individuals = unique;
+
+            let mut survivors = reinsert(&mut self.reinsert, population)?;
+            survivors.num_children = num_children;
+            return Ok(survivors);
+        }
+
+        let mut survivors = reinsert(&mut self.reinsert, population)?;
+        survivors.individuals.sort_by(|a, b| b.fitness.cmp(&a.fitness));
+        let mut kept: Vec<Individual<G, F>> = Vec::with_capacity(survivors.individuals.len());
+        for individual in std::mem::take(&mut survivors.individuals) {
+            if !self.deduplicate.is_duplicate_of(&individual.genome, kept.iter().map(|kept| &kept.genome)) {
+                kept.push(individual);
+            } else if let Some(genome) = self.deduplicate.fresh(kept.iter().map(|kept| &kept.genome))? {
+                kept.push(Individual { generation: survivors.generation, genome, fitness: None, id: None });
+                survivors.num_children += 1;
+            } else {
+                kept.push(individual);
+            }
+        }
+        survivors.individuals = kept;
+        Ok(survivors)
+    }
+}
+
+impl<R, G, F, D> ReinsertOperator<G, F> for UniqueReinserter<R, G, D>
+    where
+        G: Genotype,
+        F: Fitness,
+        R: ReinsertOperator<G, F>,
+        D: Distance<G>,
+{
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        self.reinsert_unique(population, |reinsert, population| reinsert.reinsert(population))
+    }
+
+    fn reinsert_with_parents(&mut self, population: SortedPopulation<G, F>, parents: &HashMap<u64, Vec<u64>>) -> Result<UnsortedPopulation<G, F>> {
+        self.reinsert_unique(population, |reinsert, population| reinsert.reinsert_with_parents(population, parents))
+    }
+}
+
+impl<R: Stateful, G, D> Stateful for UniqueReinserter<R, G, D> {
+    type State = R::State;
+
+    fn state(&self) -> Self::State {
+        self.reinsert.state()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.reinsert.restore(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use crate::prelude::*;
+    use super::*;
+
+    fn counter(start: i64) -> impl FnMut() -> Result<Vec<i64>> {
+        let mut next = start;
+        move || {
+            next += 1;
+            Ok(vec![next - 1])
+        }
+    }
+
+    fn genomes<S>(population: &Population<Vec<i64>, i64, S>) -> Vec<i64> {
+        population.individuals.iter().map(|individual| individual.genome[0]).collect()
+    }
+
+    // Every individual but the last two is of the previous generation.
+    fn test_population(genomes: Vec<i64>) -> SortedPopulation<Vec<i64>, i64> {
+        let len = genomes.len();
+        SortedPopulation {
+            individuals: genomes
+                .into_iter()
+                .enumerate()
+                .map(|(i, genome)| Individual { generation: (i + 2 >= len) as u64, genome: vec![genome], fitness: Some(genome), id: None })
+                .collect(),
+            generation: 1,
+            num_children: 2,
+            sorted: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_add_unique_children() {
+        let population = test_population(vec![2, 1]);
+        let mut deduplicate = Deduplicate::exact();
+        let population = population.add_unique_children(vec![vec![1], vec![3], vec![3], vec![4]], &mut deduplicate).unwrap();
+        assert_eq!(genomes(&population), vec![2, 1, 3, 4]);
+        assert_eq!(population.get_num_children(), 4);
+
+        let population = test_population(vec![2, 1]);
+        let mut deduplicate = Deduplicate::within(|a: &Vec<i64>, b: &Vec<i64>| (a[0] - b[0]).abs() as f64, 1.0)
+            .with_initializer(counter(9));
+        let population = population.add_unique_children(vec![vec![3], vec![5], vec![6]], &mut deduplicate).unwrap();
+        assert_eq!(genomes(&population), vec![2, 1, 9, 5, 11]);
+    }
+
+    #[test]
+    fn test_unique_reinserter_rejects_duplicates() {
+        let mut reinserter = UniqueReinserter::new(ElitistReinserter, Deduplicate::exact());
+        let survivors = reinserter.reinsert(test_population(vec![5, 5, 5, 4, 3])).unwrap();
+        assert_eq!(genomes(&survivors), vec![5, 4, 3]);
+        assert_eq!(survivors.get_num_children(), 2);
+    }
+
+    #[test]
+    fn test_unique_reinserter_replaces_duplicates() {
+        let deduplicate = Deduplicate::exact().with_initializer(counter(0));
+        let mut reinserter = UniqueReinserter::new(ElitistReinserter, deduplicate);
+        let survivors = reinserter.reinsert(test_population(vec![5, 5, 5, 4, 3])).unwrap();
+        assert_eq!(genomes(&survivors), vec![5, 0, 1]);
+        assert_eq!(survivors.get_num_children(), 4);
+        assert!(survivors.individuals[1..].iter().all(|individual| individual.fitness.is_none() && individual.generation == 1));
+    }
+}
@@ -1,7 +1,9 @@
 mod multiobjective;
 mod simple;
 mod pareto;
+mod niching;
 
 pub use multiobjective::*;
 pub use simple::*;
-pub use pareto::*;
\ No newline at end of file
+pub use pareto::*;
+pub use niching::*;
\ No newline at end of file
@@ -0,0 +1,459 @@
+//! Niching: fitness sharing, clearing and speciation.
+//!
+//! Each of these wraps a fitness function with a [`ScalarFitness`] and adjusts its values by
+//! how crowded the neighbourhood of every individual is, yielding a [`NicheFitness`] that
+//! orders by the adjusted value. Populations are sorted by it, so every select and reinsert
+//! operator works on the adjusted fitness and several optima can be kept at once.
+//!
+//! The adjustment depends on the whole population, which is why they must not be used in
+//! steady-state mode. Distances are measured between phenotypes, which are the genomes
+//! themselves with an [`IdentityIncubator`](crate::types::IdentityIncubator). The raw
+//! fitnesses are adjusted through their [weights](ScalarFitness::to_weight), so they must be
+//! non-negative, and minimized fitnesses work too.
+
+use std::cmp::Ordering;
+use std::sync::Mutex;
+
+use ordered_float::NotNan;
+use serde::{Deserialize, Serialize};
+
+use crate::types::*;
+use crate::error::*;
+
+/// A distance between two genomes or phenotypes.
+pub trait Distance<T> {
+    fn distance(&self, a: &T, b: &T) -> f64;
+}
+
+impl<T, D> Distance<T> for D
+    where D: Fn(&T, &T) -> f64
+{
+    fn distance(&self, a: &T, b: &T) -> f64 {
+        self(a, b)
+    }
+}
+
+/// The number of loci at which two genomes differ, plus the difference of their lengths.
+pub struct HammingDistance;
+
+impl<T: PartialEq> Distance<Vec<T>> for HammingDistance {
+    fn distance(&self, a: &Vec<T>, b: &Vec<T>) -> f64 {
+        let differences = a.iter().zip(b).filter(|(x, y)| x != y).count();
+        (differences + a.len().abs_diff(b.len())) as f64
+    }
+}
+
+/// The Euclidean distance between real-valued genomes of the same length.
+pub struct EuclideanDistance;
+
+impl Distance<Vec<f64>> for EuclideanDistance {
+    fn distance(&self, a: &Vec<f64>, b: &Vec<f64>) -> f64 {
+        a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+    }
+}
+
+/// A fitness adjusted for niching, ordered by the adjusted value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NicheFitness<F> {
+    raw: F,
+    adjusted: NotNan<f64>,
+    niche: Option<usize>,
+}
+
+impl<F> NicheFitness<F> {
+    /// The fitness before adjustment.
+    pub fn raw(&self) -> &F {
+        &self.raw
+    }
+
+    pub fn adjusted(&self) -> f64 {
+        self.adjusted.into_inner()
+    }
+
+    /// The niche of the individual, for clearing and speciation.
+    pub fn niche(&self) -> Option<usize> {
+        self.niche
+    }
+}
+
+impl<F: Ord> PartialOrd for NicheFitness<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Ord> Ord for NicheFitness<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.adjusted.cmp(&other.adjusted)
+            .then_with(|| self.raw.cmp(&other.raw))
+            .then_with(|| self.niche.cmp(&other.niche))
+    }
+}
+
+impl<F: Fitness> Fitness for NicheFitness<F> {}
+
+impl<F: Fitness> ScalarFitness for NicheFitness<F> {
+    fn to_f64(&self) -> f64 {
+        self.adjusted()
+    }
+}
+
+type PhenotypesWithFitnesses<'a, P, F> = [(&'a P, Option<&'a NicheFitness<F>>)];
+
+/// Evaluate the wrapped fitness function, passing on the raw part of existing fitnesses.
+fn evaluate_raw<FF: FitnessFunction>(fitness_function: &FF, phenotypes_with_fitnesses: &PhenotypesWithFitnesses<FF::Phenotype, FF::Fitness>) -> Result<Vec<FF::Fitness>> {
+    let phenotypes_with_raw: Vec<_> = phenotypes_with_fitnesses
+        .iter()
+        .map(|(phenotype, fitness)| (*phenotype, fitness.map(|fitness| &fitness.raw)))
+        .collect();
+    fitness_function.evaluate(&phenotypes_with_raw)
+}
+
+fn niche_fitness<F>(raw: F, adjusted: f64, niche: Option<usize>) -> Result<NicheFitness<F>> {
+    let adjusted = NotNan::new(adjusted).map_err(|_| Error::Genetic("Niching produced a NaN fitness".into()))?;
+    Ok(NicheFitness { raw, adjusted, niche })
+}
+
+/// Indices of `raw` from the best to the worst fitness.
+fn best_first<F: Ord>(raw: &[F]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..raw.len()).collect();
+    order.sort_by(|&a, &b| raw[b].cmp(&raw[a]));
+    order
+}
+
+/// Fitness sharing: the raw fitness is divided by the niche count
+/// `Σ sh(d)`, with `sh(d) = 1 - (d / radius)^alpha` for `d < radius` and 0 otherwise.
+pub struct FitnessSharing<FF, D> {
+    pub fitness_function: FF,
+    pub distance: D,
+    pub radius: f64,
+    pub alpha: f64,
+}
+
+impl<FF, D> FitnessSharing<FF, D> {
+    /// Sharing within `radius` (σ), with a triangular sharing function (α = 1).
+    pub fn new(fitness_function: FF, distance: D, radius: f64) -> Self {
+        Self { fitness_function, distance, radius, alpha: 1.0 }
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+impl<FF, D> FitnessFunction for FitnessSharing<FF, D>
+    where
+        FF: FitnessFunction,
+        FF::Fitness: ScalarFitness,
+        D: Distance<FF::Phenotype>,
+{
+    type Phenotype = FF::Phenotype;
+    type Fitness = NicheFitness<FF::Fitness>;
+
+    fn evaluate(&self, phenotypes_with_fitnesses: &[(&Self::Phenotype, Option<&Self::Fitness>)]) -> Result<Vec<Self::Fitness>> {
+        let raw = evaluate_raw(&self.fitness_function, phenotypes_with_fitnesses)?;
+        let phenotypes: Vec<_> = phenotypes_with_fitnesses.iter().map(|(phenotype, _)| *phenotype).collect();
+
+        let mut niche_counts = vec![0.0; phenotypes.len()];
+        for i in 0..phenotypes.len() {
+            // Every individual shares with itself.
+            niche_counts[i] += 1.0;
+            for j in i + 1..phenotypes.len() {
+                let distance = self.distance.distance(phenotypes[i], phenotypes[j]);
+                if distance < self.radius {
+                    let share = 1.0 - (distance / self.radius).powf(self.alpha);
+                    niche_counts[i] += share;
+                    niche_counts[j] += share;
+                }
+            }
+        }
+
+        raw.into_iter()
+            .zip(niche_counts)
+            .map(|(raw, niche_count)| {
+                let adjusted = raw.to_weight()? / niche_count;
+                niche_fitness(raw, adjusted, None)
+            })
+            .collect()
+    }
+}
+
+/// Clearing: within every niche of `radius`, only the best `capacity` individuals keep their
+/// fitness. The fitness of the others is cleared to 0.
+///
+/// Niches are formed greedily from the best individual down, every niche being centred on
+/// the best individual not yet in a niche.
+pub struct Clearing<FF, D> {
+    pub fitness_function: FF,
+    pub distance: D,
+    pub radius: f64,
+    pub capacity: usize,
+}
+
+impl<FF, D> Clearing<FF, D> {
+    /// Clearing within `radius`, keeping a single winner per niche.
+    pub fn new(fitness_function: FF, distance: D, radius: f64) -> Self {
+        Self { fitness_function, distance, radius, capacity: 1 }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+impl<FF, D> FitnessFunction for Clearing<FF, D>
+    where
+        FF: FitnessFunction,
+        FF::Fitness: ScalarFitness,
+        D: Distance<FF::Phenotype>,
+{
+    type Phenotype = FF::Phenotype;
+    type Fitness = NicheFitness<FF::Fitness>;
+
+    fn evaluate(&self, phenotypes_with_fitnesses: &[(&Self::Phenotype, Option<&Self::Fitness>)]) -> Result<Vec<Self::Fitness>> {
+        let raw = evaluate_raw(&self.fitness_function, phenotypes_with_fitnesses)?;
+        let phenotypes: Vec<_> = phenotypes_with_fitnesses.iter().map(|(phenotype, _)| *phenotype).collect();
+        let order = best_first(&raw);
+
+        let mut niches: Vec<Option<usize>> = vec![None; raw.len()];
+        let mut cleared = vec![false; raw.len()];
+        let mut num_niches = 0;
+        for (position, &centre) in order.iter().enumerate() {
+            if niches[centre].is_some() {
+                continue;
+            }
+            niches[centre] = Some(num_niches);
+            let mut winners = 1;
+            for &other in &order[position + 1..] {
+                if niches[other].is_none() && self.distance.distance(phenotypes[centre], phenotypes[other]) < self.radius {
+                    niches[other] = Some(num_niches);
+                    cleared[other] = winners >= self.capacity;
+                    winners += 1;
+                }
+            }
+            num_niches += 1;
+        }
+
+        raw.into_iter()
+            .zip(niches)
+            .zip(cleared)
+            .map(|((raw, niche), cleared)| {
+                let adjusted = if cleared { 0.0 } else { raw.to_weight()? };
+                niche_fitness(raw, adjusted, niche)
+            })
+            .collect()
+    }
+}
+
+/// A species of [`Speciation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Species<P> {
+    pub id: usize,
+    /// The genome new individuals are compared with, the best member of the last evaluation.
+    pub representative: P,
+    pub size: usize,
+}
+
+struct SpeciesState<P> {
+    species: Vec<Species<P>>,
+    next_id: usize,
+}
+
+/// NEAT-style speciation: every individual joins the first species whose representative is
+/// closer than `threshold`, or founds a new one. The adjusted fitness is the raw fitness
+/// divided by the size of the species.
+///
+/// Species persist across evaluations through their representatives, so their ids are
+/// stable from one generation to the next. Species left without members disappear.
+pub struct Speciation<FF, D, P> {
+    pub fitness_function: FF,
+    pub distance: D,
+    pub threshold: f64,
+    state: Mutex<SpeciesState<P>>,
+}
+
+impl<FF, D, P> Speciation<FF, D, P> {
+    pub fn new(fitness_function: FF, distance: D, threshold: f64) -> Self {
+        Self {
+            fitness_function,
+            distance,
+            threshold,
+            state: Mutex::new(SpeciesState { species: Vec::new(), next_id: 0 }),
+        }
+    }
+}
+
+impl<FF, D, P> Speciation<FF, D, P>
+    where P: Clone
+{
+    /// The species found in the last evaluation.
+    pub fn species(&self) -> Vec<Species<P>> {
+        self.state
+            .lock()
+            .map(|state| state.species.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl<FF, D> FitnessFunction for Speciation<FF, D, FF::Phenotype>
+    where
+        FF: FitnessFunction,
+        FF::Fitness: ScalarFitness,
+        D: Distance<FF::Phenotype>,
+{
+    type Phenotype = FF::Phenotype;
+    type Fitness = NicheFitness<FF::Fitness>;
+
+    fn evaluate(&self, phenotypes_with_fitnesses: &[(&Self::Phenotype, Option<&Self::Fitness>)]) -> Result<Vec<Self::Fitness>> {
+        let raw = evaluate_raw(&self.fitness_function, phenotypes_with_fitnesses)?;
+        let mut state = self.state
+            .lock()
+            .map_err(|_| Error::Genetic("Speciation state is poisoned".into()))?;
+
+        let mut species = std::mem::take(&mut state.species);
+        species.iter_mut().for_each(|species| species.size = 0);
+        let assignments: Vec<usize> = phenotypes_with_fitnesses
+            .iter()
+            .map(|(phenotype, _)| {
+                let index = species
+                    .iter()
+                    .position(|species| self.distance.distance(&species.representative, phenotype) < self.threshold)
+                    .unwrap_or_else(|| {
+                        species.push(Species { id: state.next_id, representative: (*phenotype).clone(), size: 0 });
+                        state.next_id += 1;
+                        species.len() - 1
+                    });
+                species[index].size += 1;
+                index
+            })
+            .collect();
+
+        // The best member of every species represents it in the next evaluation.
+        for &i in best_first(&raw).iter().rev() {
+            species[assignments[i]].representative = phenotypes_with_fitnesses[i].0.clone();
+        }
+
+        let fitnesses = raw.into_iter()
+            .zip(assignments)
+            .map(|(raw, index)| {
+                let adjusted = raw.to_weight()? / species[index].size as f64;
+                niche_fitness(raw, adjusted, Some(species[index].id))
+            })
+            .collect();
+
+        species.retain(|species| species.size > 0);
+        state.species = species;
+        fitnesses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+    use super::*;
+    use crate::fitness::SimpleFitness;
+
+    fn evaluate<FF: FitnessFunction<Phenotype = Vec<f64>>>(fitness_function: &FF, phenotypes: &[Vec<f64>]) -> Vec<FF::Fitness> {
+        let phenotypes_with_fitnesses: Vec<_> = phenotypes.iter().map(|phenotype| (phenotype, None)).collect();
+        fitness_function.evaluate(&phenotypes_with_fitnesses).unwrap()
+    }
+
+    // The raw fitness is the second gene, the first one is the position.
+    fn raw_fitness() -> impl FitnessFunction<Phenotype = Vec<f64>, Fitness = NotNan<f64>> {
+        SimpleFitness::new(|p: &Vec<f64>| Ok(NotNan::new(p[1]).unwrap())).recalculate_fitness()
+    }
+
+    fn position() -> impl Fn(&Vec<f64>, &Vec<f64>) -> f64 {
+        |a, b| (a[0] - b[0]).abs()
+    }
+
+    #[test]
+    fn test_fitness_sharing() {
+        let sharing = FitnessSharing::new(raw_fitness(), position(), 1.0);
+        let fitnesses = evaluate(&sharing, &[vec![0.0, 1.0], vec![0.5, 1.0], vec![5.0, 1.0]]);
+
+        assert!(approx_eq!(f64, fitnesses[0].adjusted(), 1.0 / 1.5));
+        assert!(approx_eq!(f64, fitnesses[1].adjusted(), 1.0 / 1.5));
+        assert!(approx_eq!(f64, fitnesses[2].adjusted(), 1.0));
+        assert_eq!(*fitnesses[2].raw(), NotNan::new(1.0).unwrap());
+        assert!(fitnesses[2] > fitnesses[0]);
+    }
+
+    #[test]
+    fn test_clearing_keeps_niche_winners() {
+        let clearing = Clearing::new(raw_fitness(), position(), 1.0);
+        let phenotypes = [vec![0.0, 3.0], vec![0.5, 2.0], vec![0.9, 1.5], vec![5.0, 1.0]];
+        let adjusted: Vec<f64> = evaluate(&clearing, &phenotypes).iter().map(NicheFitness::adjusted).collect();
+        assert_eq!(adjusted, vec![3.0, 0.0, 0.0, 1.0]);
+
+        let clearing = clearing.with_capacity(2);
+        let fitnesses = evaluate(&clearing, &phenotypes);
+        let adjusted: Vec<f64> = fitnesses.iter().map(NicheFitness::adjusted).collect();
+        assert_eq!(adjusted, vec![3.0, 2.0, 0.0, 1.0]);
+        assert_eq!(fitnesses.iter().map(NicheFitness::niche).collect::<Vec<_>>(), vec![Some(0), Some(0), Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_speciation_keeps_species_ids() {
+        let speciation = Speciation::new(raw_fitness(), position(), 1.0);
+        let fitnesses = evaluate(&speciation, &[vec![0.0, 2.0], vec![0.5, 4.0], vec![5.0, 3.0]]);
+        assert_eq!(fitnesses.iter().map(NicheFitness::niche).collect::<Vec<_>>(), vec![Some(0), Some(0), Some(1)]);
+        assert!(approx_eq!(f64, fitnesses[1].adjusted(), 2.0));
+        assert!(approx_eq!(f64, fitnesses[2].adjusted(), 3.0));
+        assert_eq!(speciation.species()[0].representative, vec![0.5, 4.0]);
+
+        // The first species is now represented by its best member at 0.5.
+        let fitnesses = evaluate(&speciation, &[vec![9.0, 1.0], vec![1.2, 1.0], vec![5.5, 1.0]]);
+        assert_eq!(fitnesses.iter().map(NicheFitness::niche).collect::<Vec<_>>(), vec![Some(2), Some(0), Some(1)]);
+        assert_eq!(speciation.species().len(), 3);
+    }
+
+    #[test]
+    fn test_sharing_keeps_both_peaks() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+        use crate::prelude::*;
+
+        // Two peaks at 2 and 8, the second one slightly higher.
+        let peaks = |p: &Vec<f64>| {
+            let peak = |centre: f64, height: f64| (height - (p[0] - centre).powi(2)).max(0.0);
+            Ok(NotNan::new(peak(2.0, 1.0) + peak(8.0, 1.1)).unwrap())
+        };
+        let run = |niching: bool| -> Vec<f64> {
+            let select = TournamentSelectionBuilder::default()
+                .with_tournament_size(3)
+                .with_num_children(10)
+                .with_rng(ChaCha8Rng::seed_from_u64(0))
+                .build();
+            let crossover = UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap();
+            let mutate = GaussianMutationBuilder::default()
+                .with_rate(MutationRate::PerGene(1.0))
+                .with_sigma(0.2)
+                .with_bounds(Bounds::Uniform(0.0, 10.0))
+                .with_rng(ChaCha8Rng::seed_from_u64(2))
+                .build()
+                .unwrap();
+            let genomes: Vec<Vec<f64>> = (0..30).map(|i| vec![i as f64 / 3.0]).collect();
+            let radius = if niching { 2.0 } else { f64::MIN_POSITIVE };
+            let builder = GeneticAlgorithmBuilder::default()
+                .with_incubator(IdentityIncubator::default())
+                .with_fitness_function(FitnessSharing::new(SimpleFitness::new(peaks).use_existing_fitness(), EuclideanDistance, radius))
+                .with_select(select)
+                .with_crossover(crossover)
+                .with_mutate(mutate)
+                .with_reinsert(ElitistReinserter);
+            let population = builder.create_population(genomes).unwrap();
+            let summary = builder.build().run(population, MaxGenerations(50)).unwrap();
+            summary.population.individuals.iter().map(|individual| individual.genome[0]).collect()
+        };
+
+        let near = |xs: &[f64], centre: f64| xs.iter().filter(|x| (*x - centre).abs() < 0.5).count();
+        let without = run(false);
+        assert_eq!(near(&without, 2.0), 0);
+        let with = run(true);
+        assert!(near(&with, 2.0) >= 5);
+        assert!(near(&with, 8.0) >= 5);
+    }
+}
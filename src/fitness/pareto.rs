@@ -1,12 +1,14 @@
 use std::marker::PhantomData;
 
+use serde::{Deserialize, Serialize};
+
 use crate::types::*;
-use rayon::slice::ParallelSliceMut;
+use rayon::prelude::*;
 use crate::error::*;
 
 pub type ObjectiveFunction<P> = Box<dyn Fn(&P)->f64>;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ParetoFitness {
     rank: usize,
     crowding_distance: f64,
@@ -29,23 +31,47 @@ impl ParetoFitness {
     }
 }
 
+/// Whether an objective is to be minimized or maximized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Direction {
+    Minimize,
+    #[default]
+    Maximize,
+}
+
+impl Direction {
+    /// The value oriented so that larger is better.
+    pub fn orient(&self, value: f64) -> f64 {
+        match self {
+            Direction::Minimize => -value,
+            Direction::Maximize => value,
+        }
+    }
+}
+
+/// Ranks phenotypes by Pareto dominance over several objectives.
+///
+/// All objectives are maximized unless [`with_directions`](ParetoFitnessFunction::with_directions)
+/// says otherwise. The objectives stored in [`ParetoFitness`] are always the values as returned
+/// by the objective functions.
 pub struct ParetoFitnessFunction<P, F> 
 {
     _phantom: PhantomData<P>,
     objectives: F,
+    directions: Vec<Direction>,
 }
 
 impl Default for ParetoFitnessFunction<(), ()>
 {
     fn default() -> Self {
-        Self { objectives: (),  _phantom: PhantomData}
+        Self { objectives: (),  _phantom: PhantomData, directions: Vec::new() }
     }
 }
 
 impl ParetoFitnessFunction<(), ()>
 {
     pub fn complex<P>() -> ParetoFitnessFunction<P, Vec<ObjectiveFunction<P>>> {
-        ParetoFitnessFunction { _phantom: PhantomData, objectives: Vec::default() }
+        ParetoFitnessFunction { _phantom: PhantomData, objectives: Vec::default(), directions: Vec::new() }
     }
 
     pub fn with_objective<P>(self, objective: Box<dyn Fn(&P)->f64>) -> ParetoFitnessFunction<P, Vec<ObjectiveFunction<P>>> 
@@ -55,7 +81,8 @@ impl ParetoFitnessFunction<(), ()>
         let objectives = vec![objective];
         ParetoFitnessFunction {
             objectives,
-            _phantom: PhantomData
+            _phantom: PhantomData,
+            directions: self.directions,
         }
     }
 
@@ -64,15 +91,39 @@ impl ParetoFitnessFunction<(), ()>
             P: Phenotype,
             F: Fn(&P)->Vec<f64>
     {
-        ParetoFitnessFunction { _phantom: PhantomData, objectives: objective }
+        ParetoFitnessFunction { _phantom: PhantomData, objectives: objective, directions: self.directions }
     }
 }
 
 impl<P, F> ParetoFitnessFunction<P, F>
 {
+    /// Set the direction of every objective, in order. Without directions, all objectives are maximized.
+    pub fn with_directions(mut self, directions: Vec<Direction>) -> Self {
+        self.directions = directions;
+        self
+    }
+
     pub fn evaluate_objectives(&self, objectives: Vec<Vec<f64>>) -> Result<Vec<ParetoFitness>> {
-        let ranks = pareto_ranks(&objectives);
-        let crowding_dists = crowding_distances(&objectives);
+        let ranks = if self.directions.is_empty() {
+            pareto_ranks(&objectives)?
+        } else {
+            if objectives.iter().any(|objectives| objectives.len() != self.directions.len()) {
+                return Err(Error::Genetic(format!("Expected {} objectives, one per direction", self.directions.len())));
+            }
+            let oriented: Vec<Vec<f64>> = objectives
+                .iter()
+                .map(|objectives| orient_objectives(&self.directions, objectives))
+                .collect();
+            pareto_ranks(&oriented)?
+        };
+        let mut crowding_dists = vec![0.0; objectives.len()];
+        // Crowding distances are only meaningful within a front.
+        for front in fronts(&ranks) {
+            let front_objectives: Vec<Vec<f64>> = front.iter().map(|&i| objectives[i].clone()).collect();
+            for (&i, distance) in front.iter().zip(crowding_distances(&front_objectives)) {
+                crowding_dists[i] = distance;
+            }
+        }
         let result = ranks.
             into_iter()
             .zip(
@@ -152,22 +203,113 @@ impl Eq for ParetoFitness {}
 
 impl PartialOrd for ParetoFitness {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.rank.partial_cmp(&other.rank) {
-            Some(std::cmp::Ordering::Equal) => other.crowding_distance.partial_cmp(&self.crowding_distance),
-            // compare based on rank first
-            Some(o) => Some(o),
-            None => None,
-        }
+        Some(self.cmp(other))
     }
 }
 
+/// The crowded-comparison operator of NSGA-II: a lower rank is better, and within
+/// the same front a larger crowding distance is better. Better fitnesses are greater,
+/// so sorted populations start with the first front.
 impl Ord for ParetoFitness {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        other.rank
+            .cmp(&self.rank)
+            .then_with(|| self.crowding_distance.total_cmp(&other.crowding_distance))
     }
 }
 
-fn pareto_ranks(vectors: &[Vec<f64>]) -> Vec<usize> {
+/// Group indices by their rank, the first front first.
+pub(crate) fn fronts(ranks: &[usize]) -> Vec<Vec<usize>> {
+    let num_fronts = ranks.iter().max().map_or(0, |max| max + 1);
+    let mut fronts = vec![Vec::new(); num_fronts];
+    for (i, &rank) in ranks.iter().enumerate() {
+        fronts[rank].push(i);
+    }
+    fronts
+}
+
+/// Whether `this` Pareto-dominates `other`, maximizing every objective.
+pub(crate) fn dominates(this: &[f64], other: &[f64]) -> bool {
+    this.iter().zip(other).all(|(a, b)| a >= b)
+        && this.iter().zip(other).any(|(a, b)| a > b)
+}
+
+/// `objectives` oriented so that every objective is maximized. Without directions, all
+/// objectives are maximized already.
+pub(crate) fn orient_objectives(directions: &[Direction], objectives: &[f64]) -> Vec<f64> {
+    if directions.is_empty() {
+        return objectives.to_vec();
+    }
+    objectives.iter().zip(directions).map(|(value, direction)| direction.orient(*value)).collect()
+}
+
+/// Fronts at least this large are scanned in parallel.
+const PARALLEL_FRONT_SIZE: usize = 2048;
+
+/// The index of the non-dominated front of every vector, 0 being the first front.
+///
+/// Uses the efficient non-dominated sort with binary search (ENS-BS): vectors are visited
+/// in decreasing lexicographic order, so a vector can only be dominated by vectors that
+/// were already assigned to a front, and its front is found by binary search over the
+/// fronts built so far. This needs far fewer comparisons than Deb's `O(MN²)` fast
+/// non-dominated sort, in particular for 2 and 3 objectives.
+///
+/// Objectives that are NaN are an error.
+pub fn pareto_ranks(vectors: &[Vec<f64>]) -> Result<Vec<usize>> {
+    if vectors.iter().flatten().any(|value| value.is_nan()) {
+        return Err(Error::Genetic("Cannot rank objectives that are NaN".into()));
+    }
+
+    // Sort with the comparison `dominates` uses, under which -0.0 equals 0.0: with `total_cmp`,
+    // a vector could be visited after one it dominates.
+    let mut order: Vec<usize> = (0..vectors.len()).collect();
+    order.par_sort_unstable_by(|&a, &b| {
+        vectors[b]
+            .iter()
+            .zip(vectors[a].iter())
+            .map(|(x, y)| x.partial_cmp(y).expect("NaN were rejected"))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let dominated_by = |front: &[usize], i: usize| {
+        if front.len() >= PARALLEL_FRONT_SIZE {
+            front.par_iter().any(|&j| dominates(&vectors[j], &vectors[i]))
+        } else {
+            // Recently added members are the most similar ones, so check them first.
+            front.iter().rev().any(|&j| dominates(&vectors[j], &vectors[i]))
+        }
+    };
+
+    let mut ranks = vec![0; vectors.len()];
+    let mut fronts: Vec<Vec<usize>> = Vec::new();
+
+    for i in order {
+        // If a vector is dominated by a member of some front, it is dominated by a
+        // member of every previous front as well, so binary search applies.
+        let (mut low, mut high) = (0, fronts.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            if dominated_by(&fronts[mid], i) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        if low == fronts.len() {
+            fronts.push(Vec::new());
+        }
+        fronts[low].push(i);
+        ranks[i] = low;
+    }
+
+    Ok(ranks)
+}
+
+/// The straightforward version of [`pareto_ranks`], which repeatedly
+/// scans the remaining vectors. Kept as a reference for tests and benchmarks.
+pub fn pareto_ranks_naive(vectors: &[Vec<f64>]) -> Vec<usize> {
     let mut indices: Vec<_> = (0..vectors.len()).collect();
     let mut ranks = vec![0; vectors.len()];
     let mut cur_rank = 0;
@@ -193,9 +335,12 @@ fn pareto_ranks(vectors: &[Vec<f64>]) -> Vec<usize> {
     ranks
 }
 
-fn crowding_distances(vectors: &[Vec<f64>]) -> Vec<f64> {
+pub(crate) fn crowding_distances(vectors: &[Vec<f64>]) -> Vec<f64> {
     let len = vectors.len();
     let mut dist = vec![0.0; len];
+    if len == 0 {
+        return dist;
+    }
     let num_objectives = vectors[0].len();
 
     for i in 0..num_objectives {
@@ -231,6 +376,7 @@ fn crowding_distances(vectors: &[Vec<f64>]) -> Vec<f64> {
 mod tests {
     use super::*;
     use float_cmp::*;
+    use proptest::prelude::*;
 
     impl Phenotype for usize {}
 
@@ -326,6 +472,39 @@ mod tests {
         assert_eq!(ranks, expected_ranks);
     }
 
+    #[test]
+    fn test_directions() {
+        let points = vec![vec![1.0, 1.0], vec![2.0, 2.0], vec![1.0, 2.0]];
+        let objectives_func = |index: &usize| points[*index].clone();
+        fn ranks<FF: FitnessFunction<Phenotype = usize, Fitness = ParetoFitness>>(fitness_function: &FF) -> Vec<usize> {
+            let phenotypes = [0, 1, 2];
+            let phenotypes_with_fitnesses: Vec<_> = phenotypes.iter().map(|p| (p, None)).collect();
+            fitness_function
+                .evaluate(&phenotypes_with_fitnesses)
+                .unwrap()
+                .into_iter()
+                .map(|fitness| fitness.rank)
+                .collect()
+        }
+
+        let maximize = ParetoFitnessFunction::default().with_objectives(objectives_func);
+        assert_eq!(ranks(&maximize), vec![2, 0, 1]);
+
+        let minimize_both = ParetoFitnessFunction::default()
+            .with_objectives(objectives_func)
+            .with_directions(vec![Direction::Minimize, Direction::Minimize]);
+        assert_eq!(ranks(&minimize_both), vec![0, 2, 1]);
+
+        let minimize_first = ParetoFitnessFunction::default()
+            .with_objectives(objectives_func)
+            .with_directions(vec![Direction::Minimize, Direction::Maximize]);
+        assert_eq!(ranks(&minimize_first), vec![1, 1, 0]);
+
+        let fitnesses = minimize_both.evaluate_objectives(points.clone()).unwrap();
+        assert_eq!(fitnesses[1].objectives(), &[2.0, 2.0]);
+        assert!(minimize_both.evaluate_objectives(vec![vec![1.0]]).is_err());
+    }
+
     #[test]
     fn test_crowding_distance() {
         let points = vec![
@@ -471,7 +650,40 @@ mod tests {
             1,
             0,
         ];        
-        assert_eq!(pareto_ranks(&points), expected);
+        assert_eq!(pareto_ranks(&points).unwrap(), expected);
+        assert_eq!(pareto_ranks_naive(&points), expected);
+    }
+
+    proptest! {
+        #[test]
+        fn test_fast_pareto_ranks_match_naive(
+            points in proptest::collection::vec(proptest::collection::vec(0..5i32, 3), 0..60)
+        ) {
+            // Small integer coordinates produce plenty of ties and duplicates.
+            let points: Vec<Vec<f64>> = points
+                .into_iter()
+                .map(|point| point.into_iter().map(f64::from).collect())
+                .collect();
+            prop_assert_eq!(pareto_ranks(&points).unwrap(), pareto_ranks_naive(&points));
+        }
+
+        #[test]
+        fn test_fast_pareto_ranks_match_naive_on_floats(
+            points in proptest::collection::vec(
+                proptest::collection::vec(prop_oneof![Just(0.0), Just(-0.0), Just(1.0), -1.0..1.0f64], 2),
+                0..60
+            )
+        ) {
+            prop_assert_eq!(pareto_ranks(&points).unwrap(), pareto_ranks_naive(&points));
+        }
+    }
+
+    #[test]
+    fn test_pareto_ranks_treat_negative_zero_as_zero() {
+        let points = vec![vec![0.0, 1.0], vec![-0.0, 2.0]];
+        assert_eq!(pareto_ranks(&points).unwrap(), vec![1, 0]);
+        assert_eq!(pareto_ranks_naive(&points), vec![1, 0]);
+        assert!(pareto_ranks(&[vec![0.0, f64::NAN]]).is_err());
     }
 
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::types::*;
 use crate::error::*;
+use crate::parallel::IndependentFitnessFunction;
 
 #[derive(Clone)]
 pub struct SimpleFitnessFunction<F, P, Fit, B>
@@ -110,3 +111,15 @@ where
             }).collect()
     }
 }
+
+impl<F, P, Fit, B> IndependentFitnessFunction for SimpleFitnessFunction<F, P, Fit, B>
+where
+    F: Fn(&P) -> Result<Fit> + Send + Sync,
+    P: Phenotype,
+    Fit: Fitness,
+    B: FitnessBehavior + Send + Sync,
+{
+    fn evaluate_one(&self, phenotype: &P, existing_fitness: Option<&Fit>) -> Result<Fit> {
+        B::handle_existing_fitness(&existing_fitness, || (self.fitness_function)(phenotype))
+    }
+}
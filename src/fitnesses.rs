@@ -1,5 +1,9 @@
 // Fitness implementations for common types
+use std::cmp::Ordering;
+
 pub use ordered_float::NotNan;
+use serde::{Deserialize, Serialize};
+
 use crate::types::*;
 
 impl Fitness for NotNan<f64> {}
@@ -14,4 +18,90 @@ impl<F1, F2> Fitness for (F1, F2)
 impl<F1, F2, F3> Fitness for (F1, F2, F3) 
     where F1: Fitness, F2: Fitness, F3: Fitness {}
 impl<F1, F2, F3, F4> Fitness for (F1, F2, F3, F4) 
-    where F1: Fitness, F2: Fitness, F3: Fitness, F4: Fitness {}
\ No newline at end of file
+    where F1: Fitness, F2: Fitness, F3: Fitness, F4: Fitness {}
+
+/// Wraps a fitness to be minimized instead of maximized, by reversing its order.
+///
+/// `Minimize(2) > Minimize(3)`, so sorted populations start with the smallest inner fitness
+/// and every selection operator favours it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Minimize<F>(pub F);
+
+impl<F> Minimize<F> {
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+}
+
+impl<F: Ord> PartialOrd for Minimize<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Ord> Ord for Minimize<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+impl<F: Fitness> Fitness for Minimize<F> {}
+
+impl<F: ScalarFitness> ScalarFitness for Minimize<F> {
+    const MINIMIZED: bool = !F::MINIMIZED;
+
+    fn to_f64(&self) -> f64 { self.0.to_f64() }
+}
+
+impl ScalarFitness for NotNan<f64> {
+    fn to_f64(&self) -> f64 { self.into_inner() }
+}
+
+impl ScalarFitness for NotNan<f32> {
+    fn to_f64(&self) -> f64 { self.into_inner() as f64 }
+}
+
+impl ScalarFitness for usize {
+    fn to_f64(&self) -> f64 { *self as f64 }
+}
+
+impl ScalarFitness for i32 {
+    fn to_f64(&self) -> f64 { *self as f64 }
+}
+
+impl ScalarFitness for u32 {
+    fn to_f64(&self) -> f64 { *self as f64 }
+}
+
+impl ScalarFitness for i64 {
+    fn to_f64(&self) -> f64 { *self as f64 }
+}
+
+impl ScalarFitness for u64 {
+    fn to_f64(&self) -> f64 { *self as f64 }
+}
+
+// Tuples compare lexicographically, so they are projected onto their most significant component.
+impl<F1, F2> ScalarFitness for (F1, F2) 
+    where F1: ScalarFitness, F2: Fitness 
+{
+    const MINIMIZED: bool = F1::MINIMIZED;
+
+    fn to_f64(&self) -> f64 { self.0.to_f64() }
+}
+
+impl<F1, F2, F3> ScalarFitness for (F1, F2, F3) 
+    where F1: ScalarFitness, F2: Fitness, F3: Fitness 
+{
+    const MINIMIZED: bool = F1::MINIMIZED;
+
+    fn to_f64(&self) -> f64 { self.0.to_f64() }
+}
+
+impl<F1, F2, F3, F4> ScalarFitness for (F1, F2, F3, F4) 
+    where F1: ScalarFitness, F2: Fitness, F3: Fitness, F4: Fitness 
+{
+    const MINIMIZED: bool = F1::MINIMIZED;
+
+    fn to_f64(&self) -> f64 { self.0.to_f64() }
+}
@@ -1,49 +1,388 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use std::marker::PhantomData;
+
 use crate::types::*;
+use crate::individual::*;
 use crate::population::*;
+use crate::termination::*;
+use crate::observer::*;
+use crate::dedup::ChildFilter;
 use crate::error::*;
 
-pub struct GeneticAlgorithm<I, F, S, C, M, R>
+/// A mutated child, with the indices of its parents in the population it was bred from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offspring<G> {
+    pub genome: G,
+    pub parents: Vec<usize>,
+    /// The id the child will carry as an [`Individual`].
+    pub id: u64,
+    /// The [name](CrossoverOperator::name) of the crossover operator that bred the child.
+    pub crossover: &'static str,
+}
+
+impl<G> Offspring<G> {
+    fn into_individual<F: Fitness>(self, generation: u64, fitness: Option<F>) -> Individual<G, F>
+        where G: Genotype
+    {
+        Individual { generation, genome: self.genome, fitness, id: Some(self.id) }
+    }
+}
+
+/// The outcome of [`GeneticAlgorithm::run`].
+pub struct RunSummary<G: Genotype, F: Fitness> {
+    pub reason: StopReason,
+    pub progress: RunProgress,
+    pub population: SortedPopulation<G, F>,
+}
+
+pub struct GeneticAlgorithm<I, F, S, C, M, R, O = (), U = ()>
 {
     pub incubator: I,
     pub fitness_function: F,
     pub select: S,
     pub crossover: C,
     pub mutate: M,
-    pub reinsert: R,    
+    pub reinsert: R,
+    pub observer: O,
+    /// Filters the children of every generation in [`advance`](Self::advance), `()` keeps them all.
+    pub deduplicate: U,
+    pub(crate) next_id: u64,
 }
 
-impl<I, F, S, C, M, R> GeneticAlgorithm<I, F, S, C, M, R>
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithm<I, F, S, C, M, R, O, U>
     where 
         I: Incubator,
         F: FitnessFunction<Phenotype = I::Phenotype>,
-        S: SelectOperator,
+        S: SelectOperator<F::Fitness>,
         C: CrossoverOperator<Genotype = I::Genotype>,
         M: MutateOperator<Genotype = I::Genotype>,
-        R: ReinsertOperator
+        R: ReinsertOperator<I::Genotype, F::Fitness>,
+        O: Observer<I::Genotype, F::Fitness>,
+        U: ChildFilter<I::Genotype>,
 {
-    pub fn advance(&mut self, population: SortedPopulation<I::Genotype, F::Fitness>) -> Result<SortedPopulation<I::Genotype, F::Fitness>> {
-        let parents = self.select.select(&population)?;
-
-        let mut offsprings = parents
+    pub fn advance(&mut self, mut population: SortedPopulation<I::Genotype, F::Fitness>) -> Result<SortedPopulation<I::Genotype, F::Fitness>> {
+        let generation = population.generation;
+        let offsprings = self.breed(&mut population)?;
+        let parents: HashMap<u64, Vec<u64>> = offsprings
+            .iter()
+            .map(|offspring| {
+                let parents = offspring.parents.iter().filter_map(|&parent| population.individuals[parent].id).collect();
+                (offspring.id, parents)
+            })
+            .collect();
+        let offsprings = offsprings
             .into_iter()
-            .map(|p| self.crossover.crossover(&p))
-            .collect::<Result<Vec<Vec<I::Genotype>>>>()?
-            .concat();
+            .map(|offspring| offspring.into_individual(generation, None))
+            .collect();
+        let existing: Vec<&I::Genotype> = population.individuals.iter().map(|individual| &individual.genome).collect();
+        let mut offsprings = self.deduplicate.filter(&existing, offsprings)?;
+        // Fresh genomes that replaced duplicates are born here.
+        for offspring in offsprings.iter_mut().filter(|offspring| offspring.id.is_none()) {
+            offspring.id = Some(self.new_id());
+        }
+
+        let population = population
+            .add_individuals(offsprings)
+            .sort(&self.incubator, &self.fitness_function)?;
+        self.observer.after_sort(&population)?;
+
+        let population = self.reinsert
+            .reinsert_with_parents(population, &parents)?
+            .sort(&self.incubator, &self.fitness_function)?;
+        self.observer.after_reinsert(&population)?;
+
+        Ok(population)
+    }
+
+    /// Advance the population generation by generation until `criterion` fires.
+    ///
+    /// The criterion is checked once before the first generation, so a population
+    /// that already satisfies it is returned unchanged.
+    pub fn run<T>(&mut self, population: SortedPopulation<I::Genotype, F::Fitness>, criterion: T) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>
+    {
+        let progress = RunProgress {
+            evaluations: population.get_num_children() as u64,
+            ..RunProgress::default()
+        };
+        self.run_from(population, progress, criterion, |_, _, _| Ok(()))
+    }
+
+    /// Continue a run from `progress`, calling `after_generation` after every generation.
+    pub(crate) fn run_from<T, A>(&mut self, population: SortedPopulation<I::Genotype, F::Fitness>, progress: RunProgress, criterion: T, after_generation: A) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>,
+            A: FnMut(&Self, &SortedPopulation<I::Genotype, F::Fitness>, &RunProgress) -> Result<()>,
+    {
+        self.run_loop(population, progress, criterion, Self::advance, after_generation)
+    }
+}
+
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithm<I, F, S, C, M, R, O, U>
+    where
+        I: Incubator,
+        F: FitnessFunction<Phenotype = I::Phenotype>,
+        S: SelectOperator<F::Fitness>,
+        C: CrossoverOperator<Genotype = I::Genotype>,
+        M: MutateOperator<Genotype = I::Genotype>,
+        O: Observer<I::Genotype, F::Fitness>,
+{
+    /// Select parents from `population`, then cross them over and mutate the offspring.
+    ///
+    /// Individuals of `population` without an id are given one first.
+    pub fn breed(&mut self, population: &mut SortedPopulation<I::Genotype, F::Fitness>) -> Result<Vec<Offspring<I::Genotype>>> {
+        self.assign_ids(population);
+        let population = &*population;
+
+        self.observer.before_select(population)?;
+        let parents = self.select.select(population)?;
+        self.observer.after_select(population, &parents)?;
+
+        let indices: HashMap<*const I::Genotype, usize> = population.individuals
+            .iter()
+            .enumerate()
+            .map(|(i, individual)| (&individual.genome as *const _, i))
+            .collect();
+
+        let mut offsprings = Vec::new();
+        let mut offspring_parents = Vec::new();
+        for group in parents.iter() {
+            let children = self.crossover.crossover(group)?;
+            let group_indices: Vec<usize> = group
+                .iter()
+                .filter_map(|parent| indices.get(&(*parent as *const _)).copied())
+                .collect();
+            offspring_parents.resize(offspring_parents.len() + children.len(), group_indices);
+            offsprings.extend(children);
+        }
+        self.observer.after_crossover(population, &offsprings)?;
 
         for genome in offsprings.iter_mut() {
             self.mutate.mutate(genome)?;
         }
+        self.observer.after_mutate(population, &offsprings)?;
 
-        
-        
-        let population = population.add_children(offsprings);
-        dbg!(population.individuals.len());
-        let population = population.sort(&self.incubator, &self.fitness_function)?;
+        let crossover = self.crossover.name();
+        let offsprings: Vec<_> = offsprings
+            .into_iter()
+            .zip(offspring_parents)
+            .map(|(genome, parents)| Offspring { genome, parents, id: self.new_id(), crossover })
+            .collect();
+        self.observer.after_breed(population, &offsprings)?;
+
+        Ok(offsprings)
+    }
+
+    /// Give an id to every individual of `population` that has none, after the ids in use.
+    fn assign_ids(&mut self, population: &mut SortedPopulation<I::Genotype, F::Fitness>) {
+        if let Some(max_id) = population.individuals.iter().filter_map(|individual| individual.id).max() {
+            self.next_id = self.next_id.max(max_id + 1);
+        }
+        for individual in population.individuals.iter_mut().filter(|individual| individual.id.is_none()) {
+            individual.id = Some(self.new_id());
+        }
+    }
 
-        
+    fn new_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+}
 
-        let population = self.reinsert.reinsert(population)?;
-        
-        population.sort(&self.incubator, &self.fitness_function)
-    }    
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithm<I, F, S, C, M, R, O, U>
+    where
+        I: Incubator,
+        F: FitnessFunction<Phenotype = I::Phenotype>,
+        S: SelectOperator<F::Fitness>,
+        C: CrossoverOperator<Genotype = I::Genotype>,
+        M: MutateOperator<Genotype = I::Genotype>,
+        R: ReplaceOperator<I::Genotype, F::Fitness>,
+        O: Observer<I::Genotype, F::Fitness>,
+{
+    /// Breed the offspring of a single selection and insert them one by one with the
+    /// replacement operator.
+    ///
+    /// Only the offspring are evaluated, and they are inserted at their sorted position, so
+    /// the other individuals keep their fitness and order. This requires a fitness function
+    /// whose values do not depend on the rest of the population, unlike
+    /// [`ParetoFitnessFunction`](crate::fitness::ParetoFitnessFunction). How many offspring
+    /// are bred per step is decided by the select and crossover operators.
+    pub fn advance_steady_state(&mut self, mut population: SortedPopulation<I::Genotype, F::Fitness>) -> Result<SortedPopulation<I::Genotype, F::Fitness>> {
+        let (offsprings, mut offspring_parents): (Vec<_>, Vec<_>) = self.breed(&mut population)?
+            .into_iter()
+            .map(|mut offspring| {
+                let parents = std::mem::take(&mut offspring.parents);
+                (offspring, parents)
+            })
+            .unzip();
+
+        let genomes: Vec<_> = offsprings.iter().map(|offspring| &offspring.genome).collect();
+        let phenotypes = self.incubator.grow_all(&genomes)?;
+        let phenotypes_with_fitnesses: Vec<_> = phenotypes.iter().map(|phenotype| (phenotype, None)).collect();
+        let fitnesses = self.fitness_function.evaluate(&phenotypes_with_fitnesses)?;
+        let offsprings: Vec<Individual<I::Genotype, F::Fitness>> = offsprings
+            .into_iter()
+            .zip(fitnesses)
+            .map(|(offspring, fitness)| offspring.into_individual(population.generation, Some(fitness)))
+            .collect();
+
+        let mut evaluated = offsprings.clone();
+        evaluated.sort_by(|a, b| b.fitness.cmp(&a.fitness));
+        self.observer.after_sort(&SortedPopulation {
+            individuals: evaluated,
+            generation: population.generation,
+            num_children: offsprings.len(),
+            sorted: PhantomData,
+        })?;
+
+        population.num_children += offsprings.len();
+        for (i, child) in offsprings.into_iter().enumerate() {
+            if let Some(victim) = self.reinsert.replace(&population, &child, &offspring_parents[i])? {
+                if victim >= population.individuals.len() {
+                    return Err(Error::Genetic(format!("Cannot replace individual {} of a population of {}", victim, population.individuals.len())));
+                }
+                let inserted = population.replace(victim, child);
+                for indices in offspring_parents[i + 1..].iter_mut() {
+                    shift_indices(indices, victim, inserted);
+                }
+            }
+        }
+        self.observer.after_reinsert(&population)?;
+
+        Ok(population)
+    }
+
+    /// Like [`run`](GeneticAlgorithm::run), but advances with
+    /// [`advance_steady_state`](GeneticAlgorithm::advance_steady_state). Every step counts as
+    /// a generation.
+    pub fn run_steady_state<T>(&mut self, population: SortedPopulation<I::Genotype, F::Fitness>, criterion: T) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>
+    {
+        let progress = RunProgress {
+            evaluations: population.get_num_children() as u64,
+            ..RunProgress::default()
+        };
+        self.run_loop(population, progress, criterion, Self::advance_steady_state, |_, _, _| Ok(()))
+    }
+}
+
+/// Update the indices of `indices` after the individual at `removed` was removed and
+/// another one inserted at `inserted`.
+fn shift_indices(indices: &mut Vec<usize>, removed: usize, inserted: usize) {
+    indices.retain(|&index| index != removed);
+    for index in indices.iter_mut() {
+        if *index > removed {
+            *index -= 1;
+        }
+        if *index >= inserted {
+            *index += 1;
+        }
+    }
+}
+
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithm<I, F, S, C, M, R, O, U>
+    where
+        I: Incubator,
+        F: FitnessFunction<Phenotype = I::Phenotype>,
+{
+    fn run_loop<T, St, A>(&mut self, mut population: SortedPopulation<I::Genotype, F::Fitness>, mut progress: RunProgress, mut criterion: T, mut step: St, mut after_generation: A) -> Result<RunSummary<I::Genotype, F::Fitness>>
+        where
+            T: TerminationCriterion<I::Genotype, F::Fitness>,
+            St: FnMut(&mut Self, SortedPopulation<I::Genotype, F::Fitness>) -> Result<SortedPopulation<I::Genotype, F::Fitness>>,
+            A: FnMut(&Self, &SortedPopulation<I::Genotype, F::Fitness>, &RunProgress) -> Result<()>,
+    {
+        let start = Instant::now();
+        let elapsed_before = progress.elapsed;
+
+        loop {
+            progress.elapsed = elapsed_before + start.elapsed();
+            if let Some(reason) = criterion.check(&population, &progress) {
+                return Ok(RunSummary { reason, progress, population });
+            }
+
+            population.next_generation();
+            population = step(self, population)?;
+
+            progress.generations += 1;
+            progress.evaluations += population.get_num_children() as u64;
+            progress.elapsed = elapsed_before + start.elapsed();
+            after_generation(self, &population, &progress)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+
+    fn run<T: TerminationCriterion<Vec<i64>, i64>>(genomes: Vec<Vec<i64>>, criterion: T) -> RunSummary<Vec<i64>, i64> {
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(|p: &Vec<i64>| Ok(p.iter().sum::<i64>())).use_existing_fitness())
+            .with_select(TournamentSelectionBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(0)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(RandomResetMutationBuilder::default()
+                .with_bounds(Bounds::Uniform(0, 1))
+                .with_rng(ChaCha8Rng::seed_from_u64(2))
+                .build()
+                .unwrap())
+            .with_reinsert(ElitistReinserter);
+        let population = builder.create_population(genomes).unwrap();
+        builder.build().run(population, criterion).unwrap()
+    }
+
+    #[test]
+    fn test_run_stops_at_max_generations() {
+        let summary = run(vec![vec![0; 64]; 10], MaxGenerations(5));
+        assert_eq!(summary.reason, StopReason::MaxGenerations(5));
+        assert_eq!(summary.progress.generations, 5);
+        assert_eq!(summary.population.individuals.len(), 10);
+        assert!(summary.progress.evaluations > 10);
+    }
+
+    #[test]
+    fn test_run_stops_at_target_fitness() {
+        let summary = run(vec![vec![0; 8]; 10], any(TargetFitness(8), MaxGenerations(200)));
+        assert_eq!(summary.reason, StopReason::TargetFitness);
+        assert!(summary.progress.generations > 0 && summary.progress.generations < 200);
+        assert_eq!(summary.population.best().fitness, Some(8));
+    }
+
+    #[test]
+    fn test_run_checks_criterion_before_first_generation() {
+        let summary = run(vec![vec![1; 8]; 10], TargetFitness(8));
+        assert_eq!(summary.reason, StopReason::TargetFitness);
+        assert_eq!(summary.progress.generations, 0);
+        assert_eq!(summary.progress.evaluations, 10);
+    }
+
+    #[test]
+    fn test_advance_deduplicates_children() {
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(|p: &Vec<i64>| Ok(p.iter().sum::<i64>())).use_existing_fitness())
+            .with_select(TournamentSelectionBuilder::default().with_num_children(8).with_rng(ChaCha8Rng::seed_from_u64(0)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(RandomResetMutationBuilder::default()
+                .with_bounds(Bounds::Uniform(0, 1))
+                .with_rng(ChaCha8Rng::seed_from_u64(2))
+                .build()
+                .unwrap())
+            .with_reinsert(ElitistReinserter)
+            .with_deduplicate(Deduplicate::exact());
+        let mut population = builder.create_population(vec![vec![0, 0, 0], vec![0, 0, 1], vec![0, 1, 0], vec![1, 0, 0]]).unwrap();
+        let mut ga = builder.build();
+        for _ in 0..10 {
+            population.next_generation();
+            population = ga.advance(population).unwrap();
+            let genomes: Vec<_> = population.individuals.iter().map(|individual| &individual.genome).collect();
+            assert!(genomes.iter().enumerate().all(|(i, genome)| !genomes[..i].contains(genome)), "{:?}", genomes);
+        }
+        assert_eq!(population.best().genome, vec![1, 1, 1]);
+    }
 }
@@ -3,10 +3,12 @@ use std::marker::PhantomData;
 use crate::population::*;
 use crate::types::*;
 use crate::ga::*;
+use crate::observer::*;
+use crate::dedup::ChildFilter;
 use crate::error::*;
 
 #[derive(Default)]
-pub struct GeneticAlgorithmBuilder<I, F, S, C, M, R>
+pub struct GeneticAlgorithmBuilder<I, F, S, C, M, R, O = (), U = ()>
 {
     pub incubator: I,
     pub fitness_function: F,
@@ -14,6 +16,8 @@ pub struct GeneticAlgorithmBuilder<I, F, S, C, M, R>
     pub crossover: C,
     pub mutate: M,
     pub reinsert: R,
+    pub observer: O,
+    pub deduplicate: U,
     _phantom: PhantomData<()>
 }
 
@@ -26,6 +30,8 @@ impl GeneticAlgorithmBuilder<(), (), (), (), (), ()> {
             crossover: (),
             mutate: (),
             reinsert: (),
+            observer: (),
+            deduplicate: (),
             _phantom: PhantomData,
         }
     }
@@ -43,15 +49,17 @@ impl<I> GeneticAlgorithmBuilder<I, (), (), (), (), ()>
             crossover: (),
             mutate: (),
             reinsert: (),
+            observer: (),
+            deduplicate: (),
             _phantom: PhantomData,
         }
     }
 }
 
-impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R> 
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithmBuilder<I, F, S, C, M, R, O, U> 
 {
 
-    pub fn with_select<SNEW>(self, select: SNEW) -> GeneticAlgorithmBuilder<I, F, SNEW, C, M, R> {
+    pub fn with_select<SNEW>(self, select: SNEW) -> GeneticAlgorithmBuilder<I, F, SNEW, C, M, R, O, U> {
         GeneticAlgorithmBuilder {
             incubator: self.incubator,
             fitness_function: self.fitness_function,
@@ -59,11 +67,13 @@ impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
             crossover: self.crossover,
             mutate: self.mutate,
             reinsert: self.reinsert,
+            observer: self.observer,
+            deduplicate: self.deduplicate,
             _phantom: PhantomData,
         }
     }
 
-    pub fn with_crossover<CNEW>(self, crossover: CNEW) -> GeneticAlgorithmBuilder<I, F, S, CNEW, M, R> {
+    pub fn with_crossover<CNEW>(self, crossover: CNEW) -> GeneticAlgorithmBuilder<I, F, S, CNEW, M, R, O, U> {
         GeneticAlgorithmBuilder {
             incubator: self.incubator,
             fitness_function: self.fitness_function,
@@ -71,11 +81,13 @@ impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
             crossover,
             mutate: self.mutate,
             reinsert: self.reinsert,
+            observer: self.observer,
+            deduplicate: self.deduplicate,
             _phantom: PhantomData,
         }        
     }
 
-    pub fn with_mutate<MNEW>(self, mutate: MNEW) -> GeneticAlgorithmBuilder<I, F, S, C, MNEW, R> {
+    pub fn with_mutate<MNEW>(self, mutate: MNEW) -> GeneticAlgorithmBuilder<I, F, S, C, MNEW, R, O, U> {
         GeneticAlgorithmBuilder {
             incubator: self.incubator,
             fitness_function: self.fitness_function,
@@ -83,11 +95,13 @@ impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
             crossover: self.crossover,
             mutate,
             reinsert: self.reinsert,
+            observer: self.observer,
+            deduplicate: self.deduplicate,
             _phantom: PhantomData,
         }        
     }
 
-    pub fn with_reinsert<RNEW>(self, reinsert: RNEW) -> GeneticAlgorithmBuilder<I, F, S, C, M, RNEW> {
+    pub fn with_reinsert<RNEW>(self, reinsert: RNEW) -> GeneticAlgorithmBuilder<I, F, S, C, M, RNEW, O, U> {
         GeneticAlgorithmBuilder {
             incubator: self.incubator,
             fitness_function: self.fitness_function,
@@ -95,19 +109,53 @@ impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
             crossover: self.crossover,
             mutate: self.mutate,
             reinsert,
+            observer: self.observer,
+            deduplicate: self.deduplicate,
             _phantom: PhantomData,
         }        
     }
+
+    /// Add an observer. Observers are called in the order they were added.
+    pub fn with_observer<ONEW>(self, observer: ONEW) -> GeneticAlgorithmBuilder<I, F, S, C, M, R, (O, ONEW), U> {
+        GeneticAlgorithmBuilder {
+            incubator: self.incubator,
+            fitness_function: self.fitness_function,
+            select: self.select,
+            crossover: self.crossover,
+            mutate: self.mutate,
+            reinsert: self.reinsert,
+            observer: (self.observer, observer),
+            deduplicate: self.deduplicate,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Filter the children of every generation before they join the population, e.g. with a
+    /// [`Deduplicate`](crate::dedup::Deduplicate) to keep duplicates out.
+    pub fn with_deduplicate<UNEW>(self, deduplicate: UNEW) -> GeneticAlgorithmBuilder<I, F, S, C, M, R, O, UNEW> {
+        GeneticAlgorithmBuilder {
+            incubator: self.incubator,
+            fitness_function: self.fitness_function,
+            select: self.select,
+            crossover: self.crossover,
+            mutate: self.mutate,
+            reinsert: self.reinsert,
+            observer: self.observer,
+            deduplicate,
+            _phantom: PhantomData,
+        }
+    }
 }
 
-impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
+impl<I, F, S, C, M, R, O, U> GeneticAlgorithmBuilder<I, F, S, C, M, R, O, U>
     where
         I: Incubator,
         F: FitnessFunction<Phenotype = I::Phenotype>,
-        S: SelectOperator,
+        S: SelectOperator<F::Fitness>,
         C: CrossoverOperator<Genotype = I::Genotype>,
         M: MutateOperator<Genotype = I::Genotype>,
-        R: ReinsertOperator
+        O: Observer<I::Genotype, F::Fitness>,
+        U: ChildFilter<I::Genotype>,
 {
     pub fn create_population(&self, genomes: Vec<I::Genotype>) -> Result<SortedPopulation<I::Genotype, F::Fitness>> 
     {
@@ -116,7 +164,7 @@ impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
             .sort(&self.incubator, &self.fitness_function)
     }
 
-    pub fn build(self) -> GeneticAlgorithm<I, F, S, C, M, R> {
+    pub fn build(self) -> GeneticAlgorithm<I, F, S, C, M, R, O, U> {
         GeneticAlgorithm {
             incubator: self.incubator,
             fitness_function: self.fitness_function,
@@ -124,6 +172,9 @@ impl<I, F, S, C, M, R> GeneticAlgorithmBuilder<I, F, S, C, M, R>
             crossover: self.crossover,
             mutate: self.mutate,
             reinsert: self.reinsert,
+            observer: self.observer,
+            deduplicate: self.deduplicate,
+            next_id: 0,
         }
     }
 }
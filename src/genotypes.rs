@@ -1,4 +1,82 @@
+use serde::Deserialize;
+
 use crate::types::*;
+use crate::error::*;
 
 impl<T> Genotype for Vec<T> where T: Clone + Send + Sync {}
-impl<T> Phenotype for Vec<T> where T: Clone + Send + Sync {}
\ No newline at end of file
+impl<T> Phenotype for Vec<T> where T: Clone + Send + Sync {}
+
+/// Inclusive lower and upper bounds of the genes of a `Vec` genotype.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Bounds<T> {
+    /// The same bounds for every gene.
+    Uniform(T, T),
+    /// Bounds for each locus.
+    PerGene(Vec<(T, T)>),
+}
+
+impl<T: Copy> Bounds<T> {
+    /// Bounds of the gene at `locus`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bounds are per gene and `locus` is out of range.
+    pub fn get(&self, locus: usize) -> (T, T) {
+        match self {
+            Bounds::Uniform(low, high) => (*low, *high),
+            Bounds::PerGene(bounds) => bounds[locus],
+        }
+    }
+}
+
+impl<T: Copy + PartialOrd> Bounds<T> {
+    /// Check that no lower bound is above its upper bound.
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            Bounds::Uniform(low, high) => low <= high,
+            Bounds::PerGene(bounds) => bounds.iter().all(|(low, high)| low <= high),
+        };
+        if !valid {
+            return Err(Error::Genetic("Lower bound above upper bound".into()));
+        }
+        Ok(())
+    }
+
+    /// Check that there are bounds for every gene of a genome of length `len`.
+    pub fn check_len(&self, len: usize) -> Result<()> {
+        match self {
+            Bounds::PerGene(bounds) if bounds.len() < len => Err(Error::Genetic(format!(
+                "Bounds for {} genes, but the genome has {}", bounds.len(), len
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A real-valued gene, for operators that do their arithmetic in `f64`.
+pub trait RealGene: Copy + PartialOrd + Send + Sync {
+    fn as_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl RealGene for f64 {
+    fn as_f64(self) -> f64 { self }
+    fn from_f64(value: f64) -> Self { value }
+}
+
+impl RealGene for f32 {
+    fn as_f64(self) -> f64 { self as f64 }
+    fn from_f64(value: f64) -> Self { value as f32 }
+}
+
+/// Clamp `value` to the bounds of `locus`, if there are any.
+pub(crate) fn clamp_gene<T: RealGene>(bounds: &Option<Bounds<T>>, locus: usize, value: f64) -> T {
+    let value = match bounds {
+        Some(bounds) => {
+            let (low, high) = bounds.get(locus);
+            value.clamp(low.as_f64(), high.as_f64())
+        },
+        None => value,
+    };
+    T::from_f64(value)
+}
@@ -0,0 +1,233 @@
+//! The best individuals ever seen during a run.
+//!
+//! [`HallOfFame`] is an [`Observer`] that looks at every evaluated population (parents and
+//! children, before reinsertion) and keeps the top N unique genomes, so the best solutions
+//! survive even if reinsertion or a recalculating fitness function loses them.
+
+use std::marker::PhantomData;
+
+use crate::types::*;
+use crate::individual::*;
+use crate::population::*;
+use crate::observer::*;
+use crate::error::*;
+
+/// A member of a [`HallOfFame`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HallOfFameEntry<G, F> {
+    pub genome: G,
+    pub fitness: F,
+    /// The generation the genome first entered the hall of fame.
+    pub generation: u64,
+}
+
+type SameGenome<G> = Box<dyn Fn(&G, &G) -> bool + Send + Sync>;
+
+/// The top `capacity` unique individuals, best first.
+///
+/// Genomes are compared with `Eq`, or by a key with [`HallOfFame::with_key`]. The hall of
+/// fame is meant to be small, so looking up a genome is a linear scan.
+pub struct HallOfFame<G, F> {
+    capacity: usize,
+    entries: Vec<HallOfFameEntry<G, F>>,
+    same_genome: SameGenome<G>,
+}
+
+impl<G, F> HallOfFame<G, F>
+    where
+        G: Genotype + Eq,
+        F: Fitness,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: Vec::new(), same_genome: Box::new(|a, b| a == b) }
+    }
+}
+
+impl<G, F> HallOfFame<G, F>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    /// Consider two genomes the same individual when their keys are equal.
+    pub fn with_key<K, KF>(capacity: usize, key: KF) -> Self
+        where
+            K: Eq,
+            KF: Fn(&G) -> K + Send + Sync + 'static,
+    {
+        Self { capacity, entries: Vec::new(), same_genome: Box::new(move |a, b| key(a) == key(b)) }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The members, best first.
+    pub fn entries(&self) -> &[HallOfFameEntry<G, F>] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> Option<&HallOfFameEntry<G, F>> {
+        self.entries.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Offer a genome found in `generation`. Returns whether it is a member afterwards.
+    ///
+    /// If the genome is already a member, it keeps the better of the two fitnesses.
+    pub fn insert(&mut self, genome: &G, fitness: &F, generation: u64) -> bool {
+        if let Some(position) = self.entries.iter().position(|entry| (self.same_genome)(&entry.genome, genome)) {
+            if *fitness > self.entries[position].fitness {
+                let mut entry = self.entries.remove(position);
+                entry.fitness = fitness.clone();
+                self.insert_sorted(entry);
+            }
+            return true;
+        }
+
+        let full = self.entries.len() >= self.capacity;
+        if self.capacity == 0 || full && self.entries.last().is_some_and(|worst| *fitness <= worst.fitness) {
+            return false;
+        }
+
+        self.insert_sorted(HallOfFameEntry { genome: genome.clone(), fitness: fitness.clone(), generation });
+        self.entries.truncate(self.capacity);
+        true
+    }
+
+    fn insert_sorted(&mut self, entry: HallOfFameEntry<G, F>) {
+        let position = self.entries.partition_point(|member| member.fitness >= entry.fitness);
+        self.entries.insert(position, entry);
+    }
+
+    /// Offer every evaluated individual of `population`.
+    pub fn update<S>(&mut self, population: &Population<G, F, S>) {
+        for individual in population.individuals.iter() {
+            if let Some(fitness) = individual.fitness.as_ref() {
+                self.insert(&individual.genome, fitness, population.generation);
+            }
+        }
+    }
+
+    /// The genomes of the members, best first.
+    pub fn genomes(&self) -> Vec<G> {
+        self.entries.iter().map(|entry| entry.genome.clone()).collect()
+    }
+
+    /// Add the members to `population` for an elitist restart.
+    ///
+    /// They keep their fitness, so a fitness function that reuses existing fitness
+    /// does not evaluate them again. They also keep the generation they were found in, so
+    /// they are not counted as children, unless they were found in the current generation.
+    pub fn seed<S>(&self, population: Population<G, F, S>) -> UnsortedPopulation<G, F> {
+        let generation = population.generation;
+        let mut individuals = population.individuals;
+        individuals.extend(self.entries.iter().map(|entry| Individual {
+            generation: entry.generation,
+            genome: entry.genome.clone(),
+            fitness: Some(entry.fitness.clone()),
+            id: None,
+        }));
+
+        UnsortedPopulation {
+            individuals,
+            generation,
+            num_children: population.num_children,
+            sorted: PhantomData,
+        }
+    }
+}
+
+impl<G, F> Observer<G, F> for HallOfFame<G, F>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    fn after_sort(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.update(population);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fitness::SimpleFitness;
+    use crate::reinsert::MuCommaLambda;
+    use super::*;
+
+    fn fitnesses(hall_of_fame: &HallOfFame<Vec<i64>, i64>) -> Vec<i64> {
+        hall_of_fame.entries().iter().map(|entry| entry.fitness).collect()
+    }
+
+    #[test]
+    fn test_hall_of_fame_keeps_top_unique() {
+        let mut hall_of_fame = HallOfFame::new(3);
+        assert!(hall_of_fame.insert(&vec![1], &1, 0));
+        assert!(hall_of_fame.insert(&vec![5], &5, 0));
+        assert!(hall_of_fame.insert(&vec![5], &5, 1));
+        assert!(hall_of_fame.insert(&vec![3], &3, 1));
+        assert!(hall_of_fame.insert(&vec![4], &4, 2));
+        assert!(!hall_of_fame.insert(&vec![0], &0, 2));
+
+        assert_eq!(fitnesses(&hall_of_fame), vec![5, 4, 3]);
+        assert_eq!(hall_of_fame.best().unwrap().generation, 0);
+
+        // A better fitness for a known genome moves it up, keeping the generation it was found in.
+        hall_of_fame.insert(&vec![3], &9, 3);
+        assert_eq!(fitnesses(&hall_of_fame), vec![9, 5, 4]);
+        assert_eq!(hall_of_fame.best().unwrap().generation, 1);
+    }
+
+    #[test]
+    fn test_hall_of_fame_with_key() {
+        let mut hall_of_fame = HallOfFame::with_key(5, |genome: &Vec<i64>| genome.iter().sum::<i64>());
+        hall_of_fame.insert(&vec![1, 2], &3, 0);
+        hall_of_fame.insert(&vec![2, 1], &3, 0);
+        hall_of_fame.insert(&vec![2, 2], &4, 0);
+        assert_eq!(hall_of_fame.genomes(), vec![vec![2, 2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut hall_of_fame = HallOfFame::new(2);
+        hall_of_fame.insert(&vec![7], &7, 0);
+
+        let population: UnsortedPopulation<Vec<i64>, i64> = UnsortedPopulation::default().add_children(vec![vec![1]]);
+        let population = hall_of_fame.seed(population);
+        assert_eq!(population.individuals.len(), 2);
+        assert_eq!(population.get_num_children(), 1);
+        assert_eq!(population.individuals[1].fitness, Some(7));
+    }
+
+    #[test]
+    fn test_seeded_members_are_not_children() {
+        let mut hall_of_fame = HallOfFame::new(1);
+        hall_of_fame.insert(&vec![9], &9, 1);
+
+        let individual = |genome: i64, generation| Individual { generation, genome: vec![genome], fitness: Some(genome), id: None };
+        let population = UnsortedPopulation {
+            individuals: vec![individual(5, 2), individual(2, 3), individual(1, 3)],
+            generation: 3,
+            num_children: 2,
+            sorted: PhantomData,
+        };
+        let population = hall_of_fame
+            .seed(population)
+            .sort(&IdentityIncubator::default(), &SimpleFitness::new(|genome: &Vec<i64>| Ok(genome[0])).use_existing_fitness())
+            .unwrap();
+
+        let survivors = MuCommaLambda::default().with_mu(2).reinsert(population).unwrap();
+        let genomes: Vec<_> = survivors.individuals.iter().map(|individual| individual.genome[0]).collect();
+        assert_eq!(genomes, vec![2, 1]);
+    }
+}
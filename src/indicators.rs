@@ -0,0 +1,294 @@
+//! Quality indicators for Pareto fronts.
+//!
+//! All indicators work on objective vectors as returned by [`ParetoFitness::objectives`]
+//! and assume, like [`pareto_ranks`](crate::fitness::pareto_ranks), that every objective is
+//! maximized. Fronts with minimized objectives can be brought into that form with [`orient`].
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+use crate::types::*;
+use crate::population::*;
+use crate::fitness::{Direction, ParetoFitness, orient_objectives};
+use crate::error::*;
+
+/// Number of samples used by [`hypervolume`] for more than 3 objectives.
+pub const DEFAULT_HYPERVOLUME_SAMPLES: usize = 100_000;
+
+/// The objectives of the first front of a population.
+pub fn first_front<G: Genotype>(population: &SortedPopulation<G, ParetoFitness>) -> Vec<Vec<f64>> {
+    population.individuals
+        .iter()
+        .filter_map(|individual| individual.fitness.as_ref())
+        .filter(|fitness| fitness.rank() == 0)
+        .map(|fitness| fitness.objectives().to_vec())
+        .collect()
+}
+
+/// Orient `points` so that every objective is maximized.
+pub fn orient(points: &[Vec<f64>], directions: &[Direction]) -> Vec<Vec<f64>> {
+    points
+        .iter()
+        .map(|point| orient_objectives(directions, point))
+        .collect()
+}
+
+fn check_dimensions(points: &[Vec<f64>], num_objectives: usize) -> Result<()> {
+    if points.iter().any(|point| point.len() != num_objectives) {
+        return Err(Error::Genetic(format!("All points must have {} objectives", num_objectives)));
+    }
+    Ok(())
+}
+
+fn check_not_empty(points: &[Vec<f64>], name: &str) -> Result<()> {
+    if points.is_empty() {
+        return Err(Error::Genetic(format!("{} must not be empty", name)));
+    }
+    Ok(())
+}
+
+fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+fn min_distance(point: &[f64], others: &[Vec<f64>]) -> f64 {
+    others
+        .iter()
+        .map(|other| euclidean_distance(point, other))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// The points that improve on `reference` in every objective.
+fn above_reference(points: &[Vec<f64>], reference: &[f64]) -> Vec<Vec<f64>> {
+    points
+        .iter()
+        .filter(|point| point.iter().zip(reference).all(|(value, bound)| value > bound))
+        .cloned()
+        .collect()
+}
+
+/// Area dominated by 2-dimensional `points`, all of which lie above `reference`.
+fn hypervolume_2d(points: &[Vec<f64>], reference: &[f64]) -> f64 {
+    let mut points: Vec<&Vec<f64>> = points.iter().collect();
+    points.sort_by(|a, b| b[0].total_cmp(&a[0]));
+
+    let mut area = 0.0;
+    let mut max_y = reference[1];
+    for point in points {
+        if point[1] > max_y {
+            area += (point[0] - reference[0]) * (point[1] - max_y);
+            max_y = point[1];
+        }
+    }
+    area
+}
+
+/// Volume dominated by 3-dimensional `points`, computed as a sum of 2-dimensional slices.
+fn hypervolume_3d(points: &[Vec<f64>], reference: &[f64]) -> f64 {
+    let mut points: Vec<&Vec<f64>> = points.iter().collect();
+    points.sort_by(|a, b| b[2].total_cmp(&a[2]));
+
+    let mut volume = 0.0;
+    let mut slice: Vec<Vec<f64>> = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        slice.push(point[..2].to_vec());
+        let next_z = points.get(i + 1).map_or(reference[2], |next| next[2]);
+        if next_z < point[2] {
+            volume += hypervolume_2d(&slice, reference) * (point[2] - next_z);
+        }
+    }
+    volume
+}
+
+/// The hypervolume dominated by `points` and bounded by `reference`.
+///
+/// Exact for up to 3 objectives. With more objectives it is estimated by
+/// [`hypervolume_monte_carlo`] with [`DEFAULT_HYPERVOLUME_SAMPLES`] samples and a fixed seed,
+/// so results are reproducible.
+pub fn hypervolume(points: &[Vec<f64>], reference: &[f64]) -> Result<f64> {
+    check_dimensions(points, reference.len())?;
+    let points = above_reference(points, reference);
+    if points.is_empty() {
+        return Ok(0.0);
+    }
+
+    match reference.len() {
+        0 => Err(Error::Genetic("Hypervolume needs at least one objective".into())),
+        1 => Ok(points.iter().map(|point| point[0] - reference[0]).fold(0.0, f64::max)),
+        2 => Ok(hypervolume_2d(&points, reference)),
+        3 => Ok(hypervolume_3d(&points, reference)),
+        _ => hypervolume_monte_carlo(&points, reference, DEFAULT_HYPERVOLUME_SAMPLES, &mut SmallRng::seed_from_u64(0)),
+    }
+}
+
+/// Monte Carlo estimate of the hypervolume dominated by `points` and bounded by `reference`,
+/// sampling `samples` points uniformly in the bounding box of the front.
+pub fn hypervolume_monte_carlo<R: Rng>(points: &[Vec<f64>], reference: &[f64], samples: usize, rng: &mut R) -> Result<f64> {
+    check_dimensions(points, reference.len())?;
+    let points = above_reference(points, reference);
+    if points.is_empty() || samples == 0 {
+        return Ok(0.0);
+    }
+
+    let upper: Vec<f64> = (0..reference.len())
+        .map(|i| points.iter().map(|point| point[i]).fold(f64::NEG_INFINITY, f64::max))
+        .collect();
+    let box_volume: f64 = upper.iter().zip(reference).map(|(high, low)| high - low).product();
+
+    let mut sample = vec![0.0; reference.len()];
+    let mut hits = 0;
+    for _ in 0..samples {
+        for (i, value) in sample.iter_mut().enumerate() {
+            *value = rng.gen_range(reference[i]..=upper[i]);
+        }
+        if points.iter().any(|point| point.iter().zip(sample.iter()).all(|(p, s)| p >= s)) {
+            hits += 1;
+        }
+    }
+
+    Ok(box_volume * hits as f64 / samples as f64)
+}
+
+/// Generational distance: the average distance of `points` to the nearest point of `reference_front`.
+pub fn generational_distance(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> Result<f64> {
+    check_not_empty(points, "Points")?;
+    check_not_empty(reference_front, "Reference front")?;
+    check_dimensions(points, reference_front[0].len())?;
+    check_dimensions(reference_front, reference_front[0].len())?;
+
+    let total: f64 = points.iter().map(|point| min_distance(point, reference_front)).sum();
+    Ok(total / points.len() as f64)
+}
+
+/// Inverted generational distance: the average distance of the points of `reference_front`
+/// to the nearest point of `points`.
+pub fn inverted_generational_distance(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> Result<f64> {
+    generational_distance(reference_front, points)
+}
+
+/// Spacing (Schott): the standard deviation of the Manhattan distances of every point
+/// to its nearest neighbour. 0 means the points are evenly spaced.
+pub fn spacing(points: &[Vec<f64>]) -> Result<f64> {
+    check_not_empty(points, "Points")?;
+    check_dimensions(points, points[0].len())?;
+    if points.len() < 2 {
+        return Ok(0.0);
+    }
+
+    let distances: Vec<f64> = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| point.iter().zip(other).map(|(a, b)| (a - b).abs()).sum::<f64>())
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect();
+
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    let variance = distances.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (distances.len() - 1) as f64;
+    Ok(variance.sqrt())
+}
+
+/// Generalized spread (Δ): combines how far `points` are from the extreme points of
+/// `reference_front` with how uniformly they are distributed. 0 is ideal.
+pub fn spread(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> Result<f64> {
+    check_not_empty(points, "Points")?;
+    check_not_empty(reference_front, "Reference front")?;
+    let num_objectives = reference_front[0].len();
+    check_dimensions(points, num_objectives)?;
+    check_dimensions(reference_front, num_objectives)?;
+
+    let extremes: f64 = (0..num_objectives)
+        .map(|i| {
+            let extreme = reference_front
+                .iter()
+                .max_by(|a, b| a[i].total_cmp(&b[i]))
+                .unwrap();
+            min_distance(extreme, points)
+        })
+        .sum();
+
+    if points.len() < 2 {
+        return Ok(if extremes > 0.0 { 1.0 } else { 0.0 });
+    }
+
+    let distances: Vec<f64> = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| euclidean_distance(point, other))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect();
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    let deviation: f64 = distances.iter().map(|d| (d - mean).abs()).sum();
+
+    let denominator = extremes + distances.len() as f64 * mean;
+    if denominator == 0.0 {
+        return Ok(0.0);
+    }
+    Ok((extremes + deviation) / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+    use super::*;
+
+    #[test]
+    fn test_hypervolume_2d() {
+        let points = vec![vec![1.0, 3.0], vec![2.0, 2.0], vec![3.0, 1.0], vec![1.0, 1.0]];
+        assert!(approx_eq!(f64, hypervolume(&points, &[0.0, 0.0]).unwrap(), 6.0));
+        // Points below the reference point do not count.
+        assert!(approx_eq!(f64, hypervolume(&points, &[1.5, 0.0]).unwrap(), 2.0));
+        assert_eq!(hypervolume(&points, &[5.0, 5.0]).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_hypervolume_3d_matches_monte_carlo() {
+        let points = vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![0.0, 0.0, 1.0],
+            vec![0.5, 0.5, 0.5],
+            vec![0.8, 0.1, 0.6],
+        ];
+        let reference = [-0.5, -0.5, -0.5];
+        let exact = hypervolume(&points, &reference).unwrap();
+        let estimate = hypervolume_monte_carlo(&points, &reference, 200_000, &mut SmallRng::seed_from_u64(1)).unwrap();
+        assert!((exact - estimate).abs() / exact < 0.01);
+
+        // A single point is just a box.
+        assert!(approx_eq!(f64, hypervolume(&[vec![1.0, 2.0, 3.0]], &[0.0, 0.0, 0.0]).unwrap(), 6.0));
+    }
+
+    #[test]
+    fn test_distances() {
+        let front = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let points = vec![vec![0.0, 0.5]];
+        assert!(approx_eq!(f64, generational_distance(&points, &front).unwrap(), 0.5));
+        assert!(approx_eq!(f64, inverted_generational_distance(&points, &front).unwrap(), (0.5 + 1.25f64.sqrt()) / 2.0));
+        assert!(generational_distance(&[], &front).is_err());
+        assert!(generational_distance(&[vec![1.0]], &front).is_err());
+    }
+
+    #[test]
+    fn test_spacing_and_spread_of_uniform_front() {
+        let front: Vec<Vec<f64>> = (0..=10).map(|i| vec![i as f64, 10.0 - i as f64]).collect();
+        assert!(approx_eq!(f64, spacing(&front).unwrap(), 0.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, spread(&front, &front).unwrap(), 0.0, epsilon = 1e-9));
+
+        let clustered = vec![vec![5.0, 5.0], vec![5.5, 4.5], vec![10.0, 0.0]];
+        assert!(spacing(&clustered).unwrap() > 0.0);
+        assert!(spread(&clustered, &front).unwrap() > 0.0);
+    }
+}
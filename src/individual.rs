@@ -1,7 +1,14 @@
+use serde::{Deserialize, Serialize};
+
 use crate::types::*;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Individual<G: Genotype, F: Fitness> {
     pub generation: u64,
     pub genome: G,
     pub fitness: Option<F>,
+    /// Identifies the individual in its lineage, see [`Genealogy`](crate::lineage::Genealogy).
+    /// Given by the genetic algorithm when breeding, unique per algorithm.
+    #[serde(default)]
+    pub id: Option<u64>,
 }
@@ -0,0 +1,469 @@
+//! Island model: several algorithms evolve separate populations and exchange migrants.
+//!
+//! Every island advances on its own thread for `interval` generations, then the best or
+//! random individuals of every island migrate to its neighbours in the
+//! [`MigrationTopology`], replacing the worst or random individuals there. Islands can use
+//! different operators, but must share the genome and fitness types, and migrants keep the
+//! fitness they had on their original island.
+
+use std::thread;
+use std::time::Instant;
+
+use rand::Rng;
+use rand::seq::index::sample;
+use serde::{Deserialize, Serialize};
+
+use crate::types::*;
+use crate::individual::*;
+use crate::population::*;
+use crate::ga::*;
+use crate::observer::*;
+use crate::dedup::ChildFilter;
+use crate::termination::*;
+use crate::error::*;
+
+/// An algorithm that can evolve the population of an island.
+pub trait Island<G, F>: Send
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    /// Advance `population` by one generation.
+    fn advance(&mut self, population: SortedPopulation<G, F>) -> Result<SortedPopulation<G, F>>;
+}
+
+impl<I, F, S, C, M, R, O, U> Island<I::Genotype, F::Fitness> for GeneticAlgorithm<I, F, S, C, M, R, O, U>
+    where
+        I: Incubator + Send,
+        F: FitnessFunction<Phenotype = I::Phenotype> + Send,
+        S: SelectOperator<F::Fitness> + Send,
+        C: CrossoverOperator<Genotype = I::Genotype> + Send,
+        M: MutateOperator<Genotype = I::Genotype> + Send,
+        R: ReinsertOperator<I::Genotype, F::Fitness> + Send,
+        O: Observer<I::Genotype, F::Fitness> + Send,
+        U: ChildFilter<I::Genotype> + Send,
+{
+    fn advance(&mut self, mut population: SortedPopulation<I::Genotype, F::Fitness>) -> Result<SortedPopulation<I::Genotype, F::Fitness>> {
+        population.next_generation();
+        GeneticAlgorithm::advance(self, population)
+    }
+}
+
+/// Which islands receive the migrants of an island.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum MigrationTopology {
+    /// Island `i` sends migrants to island `i + 1`, the last one to the first.
+    #[default]
+    Ring,
+    /// Every island sends migrants to all other islands.
+    FullyConnected,
+    /// Every island sends migrants to another island chosen at random at every migration.
+    Random,
+}
+
+/// Which individuals of an island emigrate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum MigrantSelection {
+    #[default]
+    Best,
+    Random,
+}
+
+/// Which individuals of an island are replaced by immigrants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum MigrantReplacement {
+    #[default]
+    Worst,
+    Random,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct IslandModelBuilder<R> {
+    pub topology: MigrationTopology,
+    /// Number of generations between migrations.
+    pub interval: u64,
+    /// Number of migrants an island sends to every receiving island.
+    pub num_migrants: usize,
+    pub selection: MigrantSelection,
+    pub replacement: MigrantReplacement,
+    rng: R
+}
+
+impl Default for IslandModelBuilder<()> {
+    fn default() -> Self {
+        Self {
+            topology: MigrationTopology::default(),
+            interval: 10,
+            num_migrants: 2,
+            selection: MigrantSelection::default(),
+            replacement: MigrantReplacement::default(),
+            rng: ()
+        }
+    }
+}
+
+impl<R> IslandModelBuilder<R> {
+    pub fn with_topology(mut self, topology: MigrationTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_num_migrants(mut self, num_migrants: usize) -> Self {
+        self.num_migrants = num_migrants;
+        self
+    }
+
+    pub fn with_selection(mut self, selection: MigrantSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn with_replacement(mut self, replacement: MigrantReplacement) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> IslandModelBuilder<RNG> {
+        IslandModelBuilder {
+            topology: self.topology,
+            interval: self.interval,
+            num_migrants: self.num_migrants,
+            selection: self.selection,
+            replacement: self.replacement,
+            rng
+        }
+    }
+}
+
+impl<R> IslandModelBuilder<R> where R: Rng {
+    pub fn build<G, F>(self) -> IslandModel<G, F, R>
+        where
+            G: Genotype,
+            F: Fitness,
+    {
+        IslandModel {
+            topology: self.topology,
+            interval: self.interval,
+            num_migrants: self.num_migrants,
+            selection: self.selection,
+            replacement: self.replacement,
+            rng: self.rng,
+            islands: Vec::new(),
+            populations: Vec::new(),
+            progress: RunProgress::default(),
+        }
+    }
+}
+
+pub struct IslandModel<G, F, R>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    pub topology: MigrationTopology,
+    pub interval: u64,
+    pub num_migrants: usize,
+    pub selection: MigrantSelection,
+    pub replacement: MigrantReplacement,
+    rng: R,
+    islands: Vec<Box<dyn Island<G, F>>>,
+    populations: Vec<SortedPopulation<G, F>>,
+    progress: RunProgress,
+}
+
+impl<G, F, R> IslandModel<G, F, R>
+    where
+        G: Genotype + Send,
+        F: Fitness + Send,
+        R: Rng,
+{
+    /// Add an island that evolves `population` with `algorithm`.
+    pub fn with_island<A>(mut self, algorithm: A, population: SortedPopulation<G, F>) -> Self
+        where
+            A: Island<G, F> + 'static,
+    {
+        self.progress.evaluations += population.get_num_children() as u64;
+        self.islands.push(Box::new(algorithm));
+        self.populations.push(population);
+        self
+    }
+
+    pub fn num_islands(&self) -> usize {
+        self.islands.len()
+    }
+
+    /// The current population of every island.
+    pub fn populations(&self) -> &[SortedPopulation<G, F>] {
+        &self.populations
+    }
+
+    /// The generations every island advanced and the evaluations of all islands, including
+    /// their initial populations.
+    pub fn progress(&self) -> &RunProgress {
+        &self.progress
+    }
+
+    /// The best individual across all islands.
+    pub fn best(&self) -> Option<&Individual<G, F>> {
+        self.populations
+            .iter()
+            .filter(|population| !population.individuals.is_empty())
+            .map(|population| population.best())
+            .max_by(|a, b| a.fitness.cmp(&b.fitness))
+    }
+
+    /// The population of the island holding the best individual.
+    fn best_population(&self) -> Option<&SortedPopulation<G, F>> {
+        self.populations
+            .iter()
+            .filter(|population| !population.individuals.is_empty())
+            .max_by(|a, b| a.best().fitness.cmp(&b.best().fitness))
+    }
+
+    /// Advance every island by `generations` generations, each on its own thread.
+    ///
+    /// An island that fails loses its population and is removed, and the first failure is
+    /// returned. The other islands keep the populations they evolved, so the model can go on
+    /// without the failed islands.
+    fn evolve(&mut self, generations: u64) -> Result<()> {
+        let populations = std::mem::take(&mut self.populations);
+        let results: Vec<(Result<SortedPopulation<G, F>>, u64)> = thread::scope(|scope| {
+            let handles: Vec<_> = self.islands
+                .iter_mut()
+                .zip(populations)
+                .map(|(island, mut population)| scope.spawn(move || {
+                    let mut evaluations = 0;
+                    for _ in 0..generations {
+                        population = match island.advance(population) {
+                            Ok(population) => population,
+                            Err(error) => return (Err(error), evaluations),
+                        };
+                        evaluations += population.get_num_children() as u64;
+                    }
+                    (Ok(population), evaluations)
+                }))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|_| (Err(Error::Genetic("An island thread panicked".into())), 0)))
+                .collect()
+        });
+
+        let mut failure = None;
+        let islands = std::mem::take(&mut self.islands);
+        for (index, (island, (result, evaluations))) in islands.into_iter().zip(results).enumerate() {
+            self.progress.evaluations += evaluations;
+            match result {
+                Ok(population) => {
+                    self.islands.push(island);
+                    self.populations.push(population);
+                },
+                Err(error) => {
+                    failure.get_or_insert_with(|| Error::Genetic(format!("Island {} failed and was removed: {}", index, error)));
+                },
+            }
+        }
+        if !self.islands.is_empty() {
+            self.progress.generations += generations;
+        }
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// The islands that receive the migrants of island `source`.
+    fn destinations(&mut self, source: usize) -> Vec<usize> {
+        let num_islands = self.islands.len();
+        if num_islands < 2 {
+            return Vec::new();
+        }
+        match self.topology {
+            MigrationTopology::Ring => vec![(source + 1) % num_islands],
+            MigrationTopology::FullyConnected => (0..num_islands).filter(|&i| i != source).collect(),
+            MigrationTopology::Random => {
+                let other = self.rng.gen_range(0..num_islands - 1);
+                vec![if other >= source { other + 1 } else { other }]
+            },
+        }
+    }
+
+    /// Send migrants from every island to its destinations. Migrants are selected from all
+    /// islands before any of them is received.
+    pub fn migrate(&mut self) {
+        let mut arrivals: Vec<Vec<Individual<G, F>>> = vec![Vec::new(); self.populations.len()];
+        for source in 0..self.populations.len() {
+            for destination in self.destinations(source) {
+                let migrants = select_migrants(&mut self.rng, self.selection, self.num_migrants, &self.populations[source]);
+                arrivals[destination].extend(migrants);
+            }
+        }
+
+        for (population, migrants) in self.populations.iter_mut().zip(arrivals) {
+            receive_migrants(&mut self.rng, self.replacement, population, migrants);
+        }
+    }
+
+    /// Advance all islands by `interval` generations, then migrate. If an island fails, it is
+    /// removed and the error returned without migrating.
+    pub fn advance(&mut self) -> Result<()> {
+        self.evolve(self.interval.max(1))?;
+        self.migrate();
+        Ok(())
+    }
+
+    /// Advance until `criterion` fires, checking it after every migration against the island
+    /// holding the best individual, with the progress summed over all islands.
+    pub fn run<T>(&mut self, mut criterion: T) -> Result<StopReason>
+        where
+            T: TerminationCriterion<G, F>,
+    {
+        if self.populations.is_empty() {
+            return Err(Error::Genetic("The island model has no islands".into()));
+        }
+
+        let start = Instant::now();
+        let elapsed_before = self.progress.elapsed;
+        loop {
+            self.progress.elapsed = elapsed_before + start.elapsed();
+            if let Some(population) = self.best_population() {
+                if let Some(reason) = criterion.check(population, &self.progress) {
+                    return Ok(reason);
+                }
+            }
+            self.advance()?;
+        }
+    }
+}
+
+fn select_migrants<G, F, R>(rng: &mut R, selection: MigrantSelection, num_migrants: usize, population: &SortedPopulation<G, F>) -> Vec<Individual<G, F>>
+    where
+        G: Genotype,
+        F: Fitness,
+        R: Rng,
+{
+    let amount = num_migrants.min(population.individuals.len());
+    let indices = match selection {
+        MigrantSelection::Best => (0..amount).collect(),
+        MigrantSelection::Random => sample(rng, population.individuals.len(), amount).into_vec(),
+    };
+    indices.into_iter().map(|i| population.individuals[i].clone()).collect()
+}
+
+/// Replace individuals of `population` with `migrants`, keeping its size.
+fn receive_migrants<G, F, R>(rng: &mut R, replacement: MigrantReplacement, population: &mut SortedPopulation<G, F>, migrants: Vec<Individual<G, F>>)
+    where
+        G: Genotype,
+        F: Fitness,
+        R: Rng,
+{
+    let len = population.individuals.len();
+    let amount = migrants.len().min(len);
+    let mut victims = match replacement {
+        MigrantReplacement::Worst => (len - amount..len).collect(),
+        MigrantReplacement::Random => sample(rng, len, amount).into_vec(),
+    };
+    // Remove all victims before inserting, so immigrants never replace each other.
+    victims.sort_unstable_by(|a, b| b.cmp(a));
+    for victim in victims {
+        population.individuals.remove(victim);
+    }
+    for migrant in migrants.into_iter().take(amount) {
+        population.insert(migrant);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use super::*;
+
+    fn island(seed: u64, genomes: Vec<Vec<i64>>) -> (impl Island<Vec<i64>, i64>, SortedPopulation<Vec<i64>, i64>) {
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(|p: &Vec<i64>| Ok(p.iter().sum::<i64>())).use_existing_fitness())
+            .with_select(TournamentSelectionBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(seed)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(seed + 1)).build().unwrap())
+            .with_mutate(RandomResetMutationBuilder::default()
+                .with_bounds(Bounds::Uniform(0, 1))
+                .with_rng(ChaCha8Rng::seed_from_u64(seed + 2))
+                .build()
+                .unwrap())
+            .with_reinsert(ElitistReinserter);
+        let population = builder.create_population(genomes).unwrap();
+        (builder.build(), population)
+    }
+
+    #[test]
+    fn test_ring_migration() {
+        let (a, population_a) = island(0, (0..5).map(|i| vec![i]).collect());
+        let (b, population_b) = island(0, (100..105).map(|i| vec![i]).collect());
+
+        let mut model = IslandModelBuilder::default()
+            .with_num_migrants(1)
+            .with_rng(ChaCha8Rng::seed_from_u64(0))
+            .build()
+            .with_island(a, population_a)
+            .with_island(b, population_b);
+        model.migrate();
+
+        let genomes = |i: usize| -> Vec<i64> {
+            model.populations()[i].individuals.iter().map(|individual| individual.genome[0]).collect()
+        };
+        assert_eq!(genomes(0), vec![104, 4, 3, 2, 1]);
+        assert_eq!(genomes(1), vec![104, 103, 102, 101, 4]);
+        assert_eq!(model.best().unwrap().fitness, Some(104));
+    }
+
+    #[test]
+    fn test_islands_run_in_parallel() {
+        let mut model = IslandModelBuilder::default()
+            .with_topology(MigrationTopology::Random)
+            .with_interval(5)
+            .with_rng(ChaCha8Rng::seed_from_u64(0))
+            .build();
+        for seed in 0..3 {
+            let (algorithm, population) = island(seed * 10, vec![vec![0; 8]; 10]);
+            model = model.with_island(algorithm, population);
+        }
+
+        let reason = model.run(any(TargetFitness(8), MaxGenerations(200))).unwrap();
+        assert_eq!(reason, StopReason::TargetFitness);
+        assert_eq!(model.progress().generations % 5, 0);
+        assert_eq!(model.best().unwrap().genome, vec![1; 8]);
+        assert!(model.populations().iter().all(|population| population.individuals.len() == 10));
+    }
+
+    struct Failing;
+
+    impl Island<Vec<i64>, i64> for Failing {
+        fn advance(&mut self, _population: SortedPopulation<Vec<i64>, i64>) -> Result<SortedPopulation<Vec<i64>, i64>> {
+            Err(Error::Genetic("Failing island".into()))
+        }
+    }
+
+    #[test]
+    fn test_failing_island_is_removed() {
+        let (algorithm, population) = island(0, vec![vec![0; 8]; 10]);
+        let (_, failing_population) = island(1, vec![vec![0; 8]; 10]);
+        let mut model = IslandModelBuilder::default()
+            .with_interval(2)
+            .with_rng(ChaCha8Rng::seed_from_u64(0))
+            .build()
+            .with_island(algorithm, population)
+            .with_island(Failing, failing_population);
+
+        assert!(matches!(model.advance(), Err(Error::Genetic(message)) if message.starts_with("Island 1 failed")));
+        assert_eq!(model.num_islands(), 1);
+        assert_eq!(model.populations()[0].generation, 2);
+        assert_eq!(model.progress().generations, 2);
+
+        assert_eq!(model.run(MaxGenerations(4)).unwrap(), StopReason::MaxGenerations(4));
+        assert_eq!(model.populations()[0].generation, 4);
+    }
+}
@@ -4,6 +4,7 @@ pub mod error;
 pub mod types;
 pub mod fitness;
 pub mod crossover;
+pub mod mutate;
 pub mod gabuilder;
 pub mod ga;
 pub mod individual;
@@ -12,12 +13,27 @@ pub mod reinsert;
 pub mod select;
 pub mod fitnesses;
 pub mod genotypes;
+pub mod termination;
+pub mod observer;
+pub mod statistics;
+pub mod parallel;
+pub mod checkpoint;
+pub mod recorder;
+pub mod indicators;
+pub mod archive;
+pub mod hall_of_fame;
+pub mod island;
+pub mod lineage;
+pub mod cache;
+pub mod dedup;
 
 pub mod prelude {
     //pub use super::error::*;
     pub use super::types::*;
     pub use super::fitness::*;
-    pub use super::crossover::*;
+    // The operators rather than their modules: crossover and mutate both have a `permutation` module.
+    pub use super::crossover::{unique_genes::*, exhaustive::*, point::*, permutation::*, real::*};
+    pub use super::mutate::{rate::*, bit_flip::*, random_reset::*, perturbation::*, permutation::*};
     pub use super::gabuilder::*;
     pub use super::ga::*;
     pub use super::individual::*;
@@ -26,4 +42,17 @@ pub mod prelude {
     pub use super::select::*;
     pub use super::fitnesses::*;
     pub use super::genotypes::*;
+    pub use super::termination::*;
+    pub use super::observer::*;
+    pub use super::statistics::*;
+    pub use super::parallel::*;
+    pub use super::checkpoint::*;
+    pub use super::recorder::*;
+    pub use super::indicators::*;
+    pub use super::archive::*;
+    pub use super::hall_of_fame::*;
+    pub use super::island::*;
+    pub use super::lineage::*;
+    pub use super::cache::*;
+    pub use super::dedup::*;
 }
\ No newline at end of file
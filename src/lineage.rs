@@ -0,0 +1,267 @@
+//! Ancestry of the individuals of a run.
+//!
+//! The genetic algorithm gives every individual an [`id`](crate::individual::Individual::id)
+//! when breeding. [`Genealogy`] is an [`Observer`] that records, for every id, the ids of its
+//! parents and the operators that bred it. The resulting [`Lineage`] can be narrowed down to the
+//! ancestry of an individual and exported to Graphviz DOT or JSON.
+//!
+//! Ids are unique per algorithm only: the islands of an
+//! [`IslandModel`](crate::island::IslandModel) number their individuals independently.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::*;
+use crate::population::*;
+use crate::observer::*;
+use crate::ga::Offspring;
+use crate::error::*;
+
+/// How an individual came to be.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LineageRecord {
+    pub id: u64,
+    /// The generation the individual was born in.
+    pub generation: u64,
+    /// The ids of its parents, empty for founders.
+    pub parents: Vec<u64>,
+    /// The name of the crossover operator that bred it, `None` for founders.
+    pub crossover: Option<String>,
+    /// Whether mutation changed its genome after crossover.
+    pub mutated: bool,
+}
+
+impl LineageRecord {
+    /// An individual that was not bred by the algorithm, e.g. of the initial population.
+    pub fn is_founder(&self) -> bool {
+        self.parents.is_empty()
+    }
+}
+
+/// A genealogy graph, ordered by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lineage {
+    records: BTreeMap<u64, LineageRecord>,
+}
+
+impl Lineage {
+    pub fn insert(&mut self, record: LineageRecord) {
+        self.records.insert(record.id, record);
+    }
+
+    pub fn record(&self, id: u64) -> Option<&LineageRecord> {
+        self.records.get(&id)
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &LineageRecord> {
+        self.records.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The individuals `ids` and all their known ancestors.
+    pub fn ancestry<T: IntoIterator<Item = u64>>(&self, ids: T) -> Lineage {
+        let mut ancestors = BTreeSet::new();
+        let mut pending: Vec<u64> = ids.into_iter().collect();
+        while let Some(id) = pending.pop() {
+            if let Some(record) = self.records.get(&id) {
+                if ancestors.insert(id) {
+                    pending.extend(record.parents.iter().copied());
+                }
+            }
+        }
+        Lineage {
+            records: ancestors.into_iter().map(|id| (id, self.records[&id].clone())).collect(),
+        }
+    }
+
+    /// Forget every individual that is not an ancestor of `ids`, e.g. of the current
+    /// population, to bound the memory used during a long run.
+    pub fn retain_ancestors_of<T: IntoIterator<Item = u64>>(&mut self, ids: T) {
+        *self = self.ancestry(ids);
+    }
+
+    /// The graph in Graphviz DOT, with an edge from every parent to its child. Founders are
+    /// drawn as boxes.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph genealogy {\n");
+        for record in self.records() {
+            let mut label = format!("{}\\ngeneration {}", record.id, record.generation);
+            if let Some(crossover) = &record.crossover {
+                write!(label, "\\n{}", crossover).unwrap();
+            }
+            if record.mutated {
+                label.push_str("\\nmutated");
+            }
+            let shape = if record.is_founder() { "box" } else { "ellipse" };
+            writeln!(dot, "    {} [label=\"{}\", shape={}];", record.id, label, shape).unwrap();
+        }
+        for record in self.records() {
+            for parent in record.parents.iter().filter(|parent| self.records.contains_key(parent)) {
+                writeln!(dot, "    {} -> {};", parent, record.id).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The records as a JSON array, ordered by id.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.records().collect::<Vec<_>>())?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let records: Vec<LineageRecord> = serde_json::from_str(json)?;
+        Ok(Lineage { records: records.into_iter().map(|record| (record.id, record)).collect() })
+    }
+}
+
+/// Records the [`Lineage`] of every individual bred by the algorithm.
+///
+/// Individuals met without a record, like those of the initial population or migrants, are
+/// recorded as founders.
+pub struct Genealogy<G> {
+    lineage: Lineage,
+    crossed: Vec<G>,
+}
+
+impl<G> Default for Genealogy<G> {
+    fn default() -> Self {
+        Self { lineage: Lineage::default(), crossed: Vec::new() }
+    }
+}
+
+impl<G> Genealogy<G> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lineage(&self) -> &Lineage {
+        &self.lineage
+    }
+
+    pub fn lineage_mut(&mut self) -> &mut Lineage {
+        &mut self.lineage
+    }
+
+    pub fn into_lineage(self) -> Lineage {
+        self.lineage
+    }
+}
+
+impl<G, F> Observer<G, F> for Genealogy<G>
+    where
+        G: Genotype + PartialEq,
+        F: Fitness,
+{
+    fn after_crossover(&mut self, _population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        self.crossed = offspring.to_vec();
+        Ok(())
+    }
+
+    fn after_breed(&mut self, population: &SortedPopulation<G, F>, offspring: &[Offspring<G>]) -> Result<()> {
+        for individual in population.individuals.iter() {
+            if let Some(id) = individual.id {
+                if self.lineage.record(id).is_none() {
+                    self.lineage.insert(LineageRecord {
+                        id,
+                        generation: individual.generation,
+                        parents: Vec::new(),
+                        crossover: None,
+                        mutated: false,
+                    });
+                }
+            }
+        }
+
+        let crossed = std::mem::take(&mut self.crossed);
+        for (i, child) in offspring.iter().enumerate() {
+            self.lineage.insert(LineageRecord {
+                id: child.id,
+                generation: population.generation,
+                parents: child.parents.iter().filter_map(|&parent| population.individuals[parent].id).collect(),
+                crossover: Some(child.crossover.to_string()),
+                mutated: crossed.get(i).is_some_and(|genome| *genome != child.genome),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use super::*;
+
+    fn record(id: u64, parents: Vec<u64>) -> LineageRecord {
+        let crossover = (!parents.is_empty()).then(|| "UniformCrossover".to_string());
+        LineageRecord { id, generation: parents.len() as u64, parents, crossover, mutated: id == 4 }
+    }
+
+    #[test]
+    fn test_ancestry_and_export() {
+        let mut lineage = Lineage::default();
+        for record in [record(0, vec![]), record(1, vec![]), record(2, vec![]), record(3, vec![0, 1]), record(4, vec![3, 2]), record(5, vec![1, 2])] {
+            lineage.insert(record);
+        }
+
+        let ancestry = lineage.ancestry([4]);
+        assert_eq!(ancestry.records().map(|record| record.id).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+        let dot = ancestry.to_dot();
+        assert!(dot.starts_with("digraph genealogy {"));
+        assert!(dot.contains("    4 [label=\"4\\ngeneration 2\\nUniformCrossover\\nmutated\", shape=ellipse];"));
+        assert!(dot.contains("    0 [label=\"0\\ngeneration 0\", shape=box];"));
+        assert!(dot.contains("    3 -> 4;"));
+        assert!(!dot.contains("-> 5;"));
+
+        assert_eq!(Lineage::from_json(&ancestry.to_json().unwrap()).unwrap(), ancestry);
+
+        lineage.retain_ancestors_of([5]);
+        assert_eq!(lineage.len(), 3);
+    }
+
+    #[test]
+    fn test_genealogy_traces_best_individual() {
+        let mut genealogy = Genealogy::new();
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(|p: &Vec<i64>| Ok(p.iter().sum::<i64>())).use_existing_fitness())
+            .with_select(TournamentSelectionBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(0)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(RandomResetMutationBuilder::default()
+                .with_bounds(Bounds::Uniform(0, 1))
+                .with_rng(ChaCha8Rng::seed_from_u64(2))
+                .build()
+                .unwrap())
+            .with_reinsert(ElitistReinserter)
+            .with_observer(&mut genealogy);
+        let population = builder.create_population(vec![vec![0; 8]; 10]).unwrap();
+        let summary = builder.build().run(population, any(TargetFitness(8), MaxGenerations(200))).unwrap();
+
+        let ids: HashSet<u64> = summary.population.individuals.iter().filter_map(|individual| individual.id).collect();
+        assert_eq!(ids.len(), 10);
+
+        let best = summary.population.individuals[0].id.unwrap();
+        let ancestry = genealogy.lineage().ancestry([best]);
+        let founders: Vec<_> = ancestry.records().filter(|record| record.is_founder()).collect();
+        assert!(!founders.is_empty());
+        assert!(founders.iter().all(|record| record.id < 10 && record.generation == 0));
+        assert!(ancestry.records().any(|record| record.mutated));
+        for record in ancestry.records().filter(|record| !record.is_founder()) {
+            assert_eq!(record.crossover.as_deref(), Some("UniformCrossover"));
+            assert!(record.parents.iter().all(|parent| ancestry.record(*parent).is_some()));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::MutationRate;
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BitFlipMutationBuilder<R> {
+    pub rate: MutationRate,
+    rng: R,
+}
+
+impl Default for BitFlipMutationBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            rng: (),
+        }
+    }
+}
+
+impl<R> BitFlipMutationBuilder<R> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> BitFlipMutationBuilder<RNG> {
+        BitFlipMutationBuilder {
+            rate: self.rate,
+            rng
+        }
+    }
+}
+
+impl<R> BitFlipMutationBuilder<R> where R: Rng {
+    pub fn build(self) -> Result<BitFlipMutation<R>> {
+        self.rate.validate()?;
+
+        Ok(BitFlipMutation {
+            rate: self.rate,
+            rng: self.rng
+        })
+    }
+}
+
+/// Flips the selected bits of a `Vec<bool>` genome.
+pub struct BitFlipMutation<R: Rng> {
+    pub rate: MutationRate,
+    rng: R,
+}
+
+impl<R: Rng> MutateOperator for BitFlipMutation<R> {
+    type Genotype = Vec<bool>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        for locus in self.rate.loci(genome.len(), &mut self.rng) {
+            genome[locus] = !genome[locus];
+        }
+        Ok(())
+    }
+}
+
+impl<R> Stateful for BitFlipMutation<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::*;
+
+    #[test]
+    fn test_bit_flip_mutation() {
+        let mut mutation = BitFlipMutationBuilder::default()
+            .with_rate(MutationRate::PerGene(1.0))
+            .with_rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+
+        let mut genome = vec![true, false, false, true];
+        mutation.mutate(&mut genome).unwrap();
+        assert_eq!(genome, vec![false, true, true, false]);
+
+        let mut mutation = BitFlipMutationBuilder::default()
+            .with_rate(MutationRate::PerGenome(1.0))
+            .with_rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+
+        let mut genome = vec![false; 10];
+        mutation.mutate(&mut genome).unwrap();
+        assert_eq!(genome.iter().filter(|bit| **bit).count(), 1);
+    }
+}
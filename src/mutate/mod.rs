@@ -0,0 +1,11 @@
+pub mod rate;
+pub mod bit_flip;
+pub mod random_reset;
+pub mod perturbation;
+pub mod permutation;
+
+pub use rate::*;
+pub use bit_flip::*;
+pub use random_reset::*;
+pub use perturbation::*;
+pub use permutation::*;
\ No newline at end of file
@@ -0,0 +1,409 @@
+//! Mutations that reorder genes, and so keep a permutation a permutation.
+//!
+//! These work on `Vec<T>` for any `T`. Every locus picked by the [`MutationRate`]
+//! is paired with another random locus of the genome.
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::MutationRate;
+
+/// A random locus of a genome of length `len` other than `locus`. `len` must be at least 2.
+fn other_locus<R: Rng>(rng: &mut R, len: usize, locus: usize) -> usize {
+    let other = rng.gen_range(0..len - 1);
+    if other >= locus { other + 1 } else { other }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SwapMutationBuilder<R> {
+    pub rate: MutationRate,
+    rng: R,
+}
+
+impl Default for SwapMutationBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            rng: (),
+        }
+    }
+}
+
+impl<R> SwapMutationBuilder<R> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> SwapMutationBuilder<RNG> {
+        SwapMutationBuilder {
+            rate: self.rate,
+            rng
+        }
+    }
+}
+
+impl<R> SwapMutationBuilder<R> where R: Rng {
+    pub fn build<T>(self) -> Result<SwapMutation<R, T>> {
+        self.rate.validate()?;
+
+        Ok(SwapMutation {
+            rate: self.rate,
+            rng: self.rng,
+            gene: PhantomData
+        })
+    }
+}
+
+/// Swaps each selected gene with another random gene.
+pub struct SwapMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    rng: R,
+    gene: PhantomData<T>,
+}
+
+impl<R, T> MutateOperator for SwapMutation<R, T>
+    where
+        R: Rng,
+        T: Clone + Send + Sync,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        let len = genome.len();
+        if len < 2 {
+            return Ok(());
+        }
+
+        for locus in self.rate.loci(len, &mut self.rng) {
+            let other = other_locus(&mut self.rng, len, locus);
+            genome.swap(locus, other);
+        }
+        Ok(())
+    }
+}
+
+impl<R, T> Stateful for SwapMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct InversionMutationBuilder<R> {
+    pub rate: MutationRate,
+    rng: R,
+}
+
+impl Default for InversionMutationBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            rng: (),
+        }
+    }
+}
+
+impl<R> InversionMutationBuilder<R> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> InversionMutationBuilder<RNG> {
+        InversionMutationBuilder {
+            rate: self.rate,
+            rng
+        }
+    }
+}
+
+impl<R> InversionMutationBuilder<R> where R: Rng {
+    pub fn build<T>(self) -> Result<InversionMutation<R, T>> {
+        self.rate.validate()?;
+
+        Ok(InversionMutation {
+            rate: self.rate,
+            rng: self.rng,
+            gene: PhantomData
+        })
+    }
+}
+
+/// Reverses the segment between each selected gene and another random gene.
+pub struct InversionMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    rng: R,
+    gene: PhantomData<T>,
+}
+
+impl<R, T> MutateOperator for InversionMutation<R, T>
+    where
+        R: Rng,
+        T: Clone + Send + Sync,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        let len = genome.len();
+        if len < 2 {
+            return Ok(());
+        }
+
+        for locus in self.rate.loci(len, &mut self.rng) {
+            let other = other_locus(&mut self.rng, len, locus);
+            let (start, end) = (locus.min(other), locus.max(other));
+            genome[start..=end].reverse();
+        }
+        Ok(())
+    }
+}
+
+impl<R, T> Stateful for InversionMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ScrambleMutationBuilder<R> {
+    pub rate: MutationRate,
+    rng: R,
+}
+
+impl Default for ScrambleMutationBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            rng: (),
+        }
+    }
+}
+
+impl<R> ScrambleMutationBuilder<R> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> ScrambleMutationBuilder<RNG> {
+        ScrambleMutationBuilder {
+            rate: self.rate,
+            rng
+        }
+    }
+}
+
+impl<R> ScrambleMutationBuilder<R> where R: Rng {
+    pub fn build<T>(self) -> Result<ScrambleMutation<R, T>> {
+        self.rate.validate()?;
+
+        Ok(ScrambleMutation {
+            rate: self.rate,
+            rng: self.rng,
+            gene: PhantomData
+        })
+    }
+}
+
+/// Shuffles the segment between each selected gene and another random gene.
+pub struct ScrambleMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    rng: R,
+    gene: PhantomData<T>,
+}
+
+impl<R, T> MutateOperator for ScrambleMutation<R, T>
+    where
+        R: Rng,
+        T: Clone + Send + Sync,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        let len = genome.len();
+        if len < 2 {
+            return Ok(());
+        }
+
+        for locus in self.rate.loci(len, &mut self.rng) {
+            let other = other_locus(&mut self.rng, len, locus);
+            let (start, end) = (locus.min(other), locus.max(other));
+            genome[start..=end].shuffle(&mut self.rng);
+        }
+        Ok(())
+    }
+}
+
+impl<R, T> Stateful for ScrambleMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct InsertionMutationBuilder<R> {
+    pub rate: MutationRate,
+    rng: R,
+}
+
+impl Default for InsertionMutationBuilder<()> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            rng: (),
+        }
+    }
+}
+
+impl<R> InsertionMutationBuilder<R> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> InsertionMutationBuilder<RNG> {
+        InsertionMutationBuilder {
+            rate: self.rate,
+            rng
+        }
+    }
+}
+
+impl<R> InsertionMutationBuilder<R> where R: Rng {
+    pub fn build<T>(self) -> Result<InsertionMutation<R, T>> {
+        self.rate.validate()?;
+
+        Ok(InsertionMutation {
+            rate: self.rate,
+            rng: self.rng,
+            gene: PhantomData
+        })
+    }
+}
+
+/// Moves each selected gene to another random position, shifting the genes in between.
+pub struct InsertionMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    rng: R,
+    gene: PhantomData<T>,
+}
+
+impl<R, T> MutateOperator for InsertionMutation<R, T>
+    where
+        R: Rng,
+        T: Clone + Send + Sync,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        let len = genome.len();
+        if len < 2 {
+            return Ok(());
+        }
+
+        for locus in self.rate.loci(len, &mut self.rng) {
+            let other = other_locus(&mut self.rng, len, locus);
+            let gene = genome.remove(locus);
+            genome.insert(other, gene);
+        }
+        Ok(())
+    }
+}
+
+impl<R, T> Stateful for InsertionMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use proptest::prelude::*;
+    use super::*;
+
+    fn assert_permutation(genome: &[usize], len: usize) {
+        let mut sorted = genome.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, (0..len).collect::<Vec<_>>());
+    }
+
+    proptest! {
+        #[test]
+        fn test_permutation_mutations_preserve_genes(len in 0usize..30, seed in 0u64..1000) {
+            let rate = MutationRate::PerGene(0.3);
+            let mut swap = SwapMutationBuilder::default().with_rate(rate).with_rng(StdRng::seed_from_u64(seed)).build().unwrap();
+            let mut inversion = InversionMutationBuilder::default().with_rate(rate).with_rng(StdRng::seed_from_u64(seed)).build().unwrap();
+            let mut scramble = ScrambleMutationBuilder::default().with_rate(rate).with_rng(StdRng::seed_from_u64(seed)).build().unwrap();
+            let mut insertion = InsertionMutationBuilder::default().with_rate(rate).with_rng(StdRng::seed_from_u64(seed)).build().unwrap();
+
+            let operators: [&mut dyn MutateOperator<Genotype = Vec<usize>>; 4] = [&mut swap, &mut inversion, &mut scramble, &mut insertion];
+            for operator in operators {
+                let mut genome: Vec<usize> = (0..len).collect();
+                operator.mutate(&mut genome).unwrap();
+                assert_permutation(&genome, len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_insertion_mutation() {
+        let mut insertion = InsertionMutationBuilder::default()
+            .with_rate(MutationRate::PerGenome(1.0))
+            .with_rng(StdRng::seed_from_u64(3))
+            .build()
+            .unwrap();
+
+        let mut genome: Vec<usize> = (0..8).collect();
+        insertion.mutate(&mut genome).unwrap();
+
+        let displaced = genome.iter().enumerate().filter(|(i, gene)| *i != **gene).count();
+        assert!(displaced >= 2);
+        assert_permutation(&genome, 8);
+    }
+}
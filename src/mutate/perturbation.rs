@@ -0,0 +1,412 @@
+//! Mutations that perturb real-valued genes.
+//!
+//! All of them work on `Vec<T>` where `T` is a [`RealGene`] (`f32` or `f64`),
+//! and clamp the mutated genes to the bounds if any are given.
+
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::genotypes::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::MutationRate;
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct GaussianMutationBuilder<R, T> {
+    pub rate: MutationRate,
+    pub sigma: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for GaussianMutationBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            sigma: 0.1,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> GaussianMutationBuilder<R, T> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_sigma(mut self, sigma: f64) -> Self {
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> GaussianMutationBuilder<RNG, T> {
+        GaussianMutationBuilder {
+            rate: self.rate,
+            sigma: self.sigma,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> GaussianMutationBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<GaussianMutation<R, T>> {
+        self.rate.validate()?;
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(GaussianMutation {
+            rate: self.rate,
+            sigma: self.sigma,
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Adds normally distributed noise with standard deviation `sigma` to the selected genes.
+pub struct GaussianMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    pub sigma: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> MutateOperator for GaussianMutation<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(genome.len())?;
+        }
+        for locus in self.rate.loci(genome.len(), &mut self.rng) {
+            let noise: f64 = self.rng.sample(StandardNormal);
+            genome[locus] = clamp_gene(&self.bounds, locus, genome[locus].as_f64() + noise * self.sigma);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct UniformPerturbationBuilder<R, T> {
+    pub rate: MutationRate,
+    pub delta: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for UniformPerturbationBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            delta: 0.1,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> UniformPerturbationBuilder<R, T> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_delta(mut self, delta: f64) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> UniformPerturbationBuilder<RNG, T> {
+        UniformPerturbationBuilder {
+            rate: self.rate,
+            delta: self.delta,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> UniformPerturbationBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<UniformPerturbation<R, T>> {
+        self.rate.validate()?;
+        if !(self.delta.is_finite() && self.delta >= 0.0) {
+            return Err(Error::Genetic(format!("Perturbation delta {} is not a finite non-negative number", self.delta)));
+        }
+        if let Some(bounds) = &self.bounds {
+            bounds.validate()?;
+        }
+
+        Ok(UniformPerturbation {
+            rate: self.rate,
+            delta: self.delta,
+            bounds: self.bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Adds noise drawn uniformly from `[-delta, delta]` to the selected genes.
+pub struct UniformPerturbation<R: Rng, T> {
+    pub rate: MutationRate,
+    pub delta: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<R, T> MutateOperator for UniformPerturbation<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        if let Some(bounds) = &self.bounds {
+            bounds.check_len(genome.len())?;
+        }
+        for locus in self.rate.loci(genome.len(), &mut self.rng) {
+            let noise = self.rng.gen_range(-self.delta..=self.delta);
+            genome[locus] = clamp_gene(&self.bounds, locus, genome[locus].as_f64() + noise);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PolynomialMutationBuilder<R, T> {
+    pub rate: MutationRate,
+    pub distribution_index: f64,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for PolynomialMutationBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            distribution_index: 20.0,
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> PolynomialMutationBuilder<R, T> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_distribution_index(mut self, distribution_index: f64) -> Self {
+        self.distribution_index = distribution_index;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> PolynomialMutationBuilder<RNG, T> {
+        PolynomialMutationBuilder {
+            rate: self.rate,
+            distribution_index: self.distribution_index,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> PolynomialMutationBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<PolynomialMutation<R, T>> {
+        let bounds = self.bounds
+            .ok_or_else(|| Error::Genetic("Polynomial mutation requires bounds".into()))?;
+        bounds.validate()?;
+        self.rate.validate()?;
+
+        Ok(PolynomialMutation {
+            rate: self.rate,
+            distribution_index: self.distribution_index,
+            bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Deb's bounded polynomial mutation. Larger distribution indices keep the
+/// mutated genes closer to their original values.
+pub struct PolynomialMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    pub distribution_index: f64,
+    pub bounds: Bounds<T>,
+    rng: R,
+}
+
+impl<R, T> MutateOperator for PolynomialMutation<R, T>
+    where
+        R: Rng,
+        T: RealGene,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        self.bounds.check_len(genome.len())?;
+        let eta = self.distribution_index;
+        let power = 1.0 / (eta + 1.0);
+
+        for locus in self.rate.loci(genome.len(), &mut self.rng) {
+            let (low, high) = self.bounds.get(locus);
+            let (low, high) = (low.as_f64(), high.as_f64());
+            if high <= low {
+                continue;
+            }
+
+            let value = genome[locus].as_f64();
+            let delta1 = (value - low) / (high - low);
+            let delta2 = (high - value) / (high - low);
+            let r: f64 = self.rng.gen();
+
+            let delta = if r < 0.5 {
+                let base = 2.0 * r + (1.0 - 2.0 * r) * (1.0 - delta1).powf(eta + 1.0);
+                base.powf(power) - 1.0
+            } else {
+                let base = 2.0 * (1.0 - r) + 2.0 * (r - 0.5) * (1.0 - delta2).powf(eta + 1.0);
+                1.0 - base.powf(power)
+            };
+
+            genome[locus] = T::from_f64((value + delta * (high - low)).clamp(low, high));
+        }
+        Ok(())
+    }
+}
+
+impl<R, T> Stateful for GaussianMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, T> Stateful for UniformPerturbation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R, T> Stateful for PolynomialMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::*;
+
+    #[test]
+    fn test_perturbations_respect_bounds() {
+        let bounds = Bounds::PerGene(vec![(0.0, 1.0), (-1.0, 0.0), (10.0, 20.0)]);
+
+        let mut gaussian = GaussianMutationBuilder::default()
+            .with_rate(MutationRate::PerGene(1.0))
+            .with_sigma(5.0)
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(0))
+            .build()
+            .unwrap();
+
+        let mut uniform = UniformPerturbationBuilder::default()
+            .with_rate(MutationRate::PerGene(1.0))
+            .with_delta(5.0)
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(1))
+            .build()
+            .unwrap();
+
+        let mut polynomial = PolynomialMutationBuilder::default()
+            .with_rate(MutationRate::PerGene(1.0))
+            .with_bounds(bounds.clone())
+            .with_rng(StdRng::seed_from_u64(2))
+            .build()
+            .unwrap();
+
+        let operators: [&mut dyn MutateOperator<Genotype = Vec<f64>>; 3] = [&mut gaussian, &mut uniform, &mut polynomial];
+        for operator in operators {
+            let mut genome = vec![0.5, -0.5, 15.0];
+            for _ in 0..100 {
+                operator.mutate(&mut genome).unwrap();
+                for (locus, gene) in genome.iter().enumerate() {
+                    let (low, high) = bounds.get(locus);
+                    assert!(*gene >= low && *gene <= high);
+                }
+            }
+            assert_ne!(genome, vec![0.5, -0.5, 15.0]);
+        }
+    }
+
+    #[test]
+    fn test_polynomial_mutation_requires_bounds() {
+        let builder = PolynomialMutationBuilder::<_, f64>::default().with_rng(StdRng::seed_from_u64(0));
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_uniform_perturbation_rejects_invalid_delta() {
+        for delta in [-1.0, f64::NAN, f64::INFINITY] {
+            let perturbation = UniformPerturbationBuilder::<_, f64>::default()
+                .with_delta(delta)
+                .with_rng(StdRng::seed_from_u64(0))
+                .build();
+            assert!(perturbation.is_err(), "{}", delta);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use rand::Rng;
+use rand::distributions::uniform::SampleUniform;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::genotypes::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::MutationRate;
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct RandomResetMutationBuilder<R, T> {
+    pub rate: MutationRate,
+    pub bounds: Option<Bounds<T>>,
+    rng: R,
+}
+
+impl<T> Default for RandomResetMutationBuilder<(), T> {
+    fn default() -> Self {
+        Self {
+            rate: MutationRate::default(),
+            bounds: None,
+            rng: (),
+        }
+    }
+}
+
+impl<R, T> RandomResetMutationBuilder<R, T> {
+    pub fn with_rate(mut self, rate: MutationRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<T>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> RandomResetMutationBuilder<RNG, T> {
+        RandomResetMutationBuilder {
+            rate: self.rate,
+            bounds: self.bounds,
+            rng
+        }
+    }
+}
+
+impl<R, T> RandomResetMutationBuilder<R, T> where R: Rng, T: Copy + PartialOrd {
+    pub fn build(self) -> Result<RandomResetMutation<R, T>> {
+        let bounds = self.bounds
+            .ok_or_else(|| Error::Genetic("Random reset mutation requires bounds".into()))?;
+        bounds.validate()?;
+        self.rate.validate()?;
+
+        Ok(RandomResetMutation {
+            rate: self.rate,
+            bounds,
+            rng: self.rng
+        })
+    }
+}
+
+/// Replaces the selected genes with a value drawn uniformly from their bounds.
+pub struct RandomResetMutation<R: Rng, T> {
+    pub rate: MutationRate,
+    pub bounds: Bounds<T>,
+    rng: R,
+}
+
+impl<R, T> MutateOperator for RandomResetMutation<R, T>
+    where
+        R: Rng,
+        T: SampleUniform + PartialOrd + Copy + Send + Sync,
+{
+    type Genotype = Vec<T>;
+
+    fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+        self.bounds.check_len(genome.len())?;
+        for locus in self.rate.loci(genome.len(), &mut self.rng) {
+            let (low, high) = self.bounds.get(locus);
+            genome[locus] = self.rng.gen_range(low..=high);
+        }
+        Ok(())
+    }
+}
+
+impl<R, T> Stateful for RandomResetMutation<R, T>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::*;
+
+    fn random_reset(rate: MutationRate, bounds: Bounds<i64>) -> Result<RandomResetMutation<StdRng, i64>> {
+        RandomResetMutationBuilder::default()
+            .with_rate(rate)
+            .with_bounds(bounds)
+            .with_rng(StdRng::seed_from_u64(0))
+            .build()
+    }
+
+    #[test]
+    fn test_random_reset_mutation() {
+        let bounds = Bounds::PerGene(vec![(0, 1), (10, 10), (-5, 5)]);
+        let mut mutation = random_reset(MutationRate::PerGene(1.0), bounds.clone()).unwrap();
+        let mut genome = vec![100, 100, 100];
+        for _ in 0..20 {
+            mutation.mutate(&mut genome).unwrap();
+            for (locus, gene) in genome.iter().enumerate() {
+                let (low, high) = bounds.get(locus);
+                assert!(*gene >= low && *gene <= high);
+            }
+        }
+
+        assert!(mutation.mutate(&mut vec![0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_random_reset_mutation_rejects_invalid_settings() {
+        assert!(random_reset(MutationRate::PerGene(1.5), Bounds::Uniform(0, 1)).is_err());
+        assert!(random_reset(MutationRate::PerGenome(-0.1), Bounds::Uniform(0, 1)).is_err());
+        assert!(random_reset(MutationRate::PerGene(0.5), Bounds::Uniform(1, 0)).is_err());
+        assert!(random_reset(MutationRate::PerGene(0.5), Bounds::PerGene(vec![(0, 1), (3, 2)])).is_err());
+    }
+}
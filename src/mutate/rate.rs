@@ -0,0 +1,53 @@
+use rand::Rng;
+use rand::seq::index;
+use serde::Deserialize;
+
+use crate::error::*;
+
+/// How often a mutation operator mutates a genome.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum MutationRate {
+    /// Every locus is mutated independently with the given probability.
+    PerGene(f64),
+    /// With the given probability, a single random locus of the genome is mutated.
+    PerGenome(f64),
+}
+
+impl Default for MutationRate {
+    fn default() -> Self {
+        MutationRate::PerGene(0.01)
+    }
+}
+
+impl MutationRate {
+    /// Check that the probability is within `[0, 1]`.
+    pub fn validate(&self) -> Result<()> {
+        let probability = match *self {
+            MutationRate::PerGene(probability) | MutationRate::PerGenome(probability) => probability,
+        };
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(Error::Genetic(format!("Mutation rate {} is not a probability", probability)));
+        }
+        Ok(())
+    }
+
+    /// Pick the loci of a genome of length `len` to mutate, in ascending order.
+    pub fn loci<R: Rng>(&self, len: usize, rng: &mut R) -> Vec<usize> {
+        if len == 0 {
+            return Vec::new();
+        }
+
+        match *self {
+            MutationRate::PerGene(probability) => (0..len)
+                .filter(|_| rng.gen_bool(probability))
+                .collect(),
+            MutationRate::PerGenome(probability) => {
+                if rng.gen_bool(probability) {
+                    index::sample(rng, len, 1).into_vec()
+                } else {
+                    Vec::new()
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Hooks into the stages of [`GeneticAlgorithm::advance`](crate::ga::GeneticAlgorithm::advance).
+//!
+//! Observers are registered with
+//! [`GeneticAlgorithmBuilder::with_observer`](crate::gabuilder::GeneticAlgorithmBuilder::with_observer).
+//! Any number of them can be combined, they are called in the order they were added.
+
+use crate::types::*;
+use crate::population::*;
+use crate::ga::Offspring;
+use crate::error::*;
+
+/// Per-stage callbacks of a generation. Every callback defaults to doing nothing.
+pub trait Observer<G, F>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    /// Called at the start of a generation, before parents are selected.
+    fn before_select(&mut self, _population: &SortedPopulation<G, F>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after parents have been selected from `population`.
+    fn after_select(&mut self, _population: &SortedPopulation<G, F>, _parents: &[Vec<&G>]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after crossover, before the offspring are mutated.
+    fn after_crossover(&mut self, _population: &SortedPopulation<G, F>, _offspring: &[G]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the offspring have been mutated.
+    fn after_mutate(&mut self, _population: &SortedPopulation<G, F>, _offspring: &[G]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once breeding is done, with the offspring, their ids and their parents in `population`.
+    fn after_breed(&mut self, _population: &SortedPopulation<G, F>, _offspring: &[Offspring<G>]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the offspring have been added to the population and everything was evaluated and sorted.
+    ///
+    /// In steady-state mode only the offspring are evaluated, and `population` holds just them.
+    fn after_sort(&mut self, _population: &SortedPopulation<G, F>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the next generation, after reinsertion.
+    fn after_reinsert(&mut self, _population: &SortedPopulation<G, F>) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<G, F> Observer<G, F> for ()
+    where
+        G: Genotype,
+        F: Fitness,
+{}
+
+impl<G, F, A, B> Observer<G, F> for (A, B)
+    where
+        G: Genotype,
+        F: Fitness,
+        A: Observer<G, F>,
+        B: Observer<G, F>,
+{
+    fn before_select(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.0.before_select(population)?;
+        self.1.before_select(population)
+    }
+
+    fn after_select(&mut self, population: &SortedPopulation<G, F>, parents: &[Vec<&G>]) -> Result<()> {
+        self.0.after_select(population, parents)?;
+        self.1.after_select(population, parents)
+    }
+
+    fn after_crossover(&mut self, population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        self.0.after_crossover(population, offspring)?;
+        self.1.after_crossover(population, offspring)
+    }
+
+    fn after_mutate(&mut self, population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        self.0.after_mutate(population, offspring)?;
+        self.1.after_mutate(population, offspring)
+    }
+
+    fn after_breed(&mut self, population: &SortedPopulation<G, F>, offspring: &[Offspring<G>]) -> Result<()> {
+        self.0.after_breed(population, offspring)?;
+        self.1.after_breed(population, offspring)
+    }
+
+    fn after_sort(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.0.after_sort(population)?;
+        self.1.after_sort(population)
+    }
+
+    fn after_reinsert(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.0.after_reinsert(population)?;
+        self.1.after_reinsert(population)
+    }
+}
+
+/// Lets an observer be borrowed by the algorithm and inspected once it is dropped.
+impl<G, F, O> Observer<G, F> for &mut O
+    where
+        G: Genotype,
+        F: Fitness,
+        O: Observer<G, F> + ?Sized,
+{
+    fn before_select(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        (**self).before_select(population)
+    }
+
+    fn after_select(&mut self, population: &SortedPopulation<G, F>, parents: &[Vec<&G>]) -> Result<()> {
+        (**self).after_select(population, parents)
+    }
+
+    fn after_crossover(&mut self, population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        (**self).after_crossover(population, offspring)
+    }
+
+    fn after_mutate(&mut self, population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        (**self).after_mutate(population, offspring)
+    }
+
+    fn after_breed(&mut self, population: &SortedPopulation<G, F>, offspring: &[Offspring<G>]) -> Result<()> {
+        (**self).after_breed(population, offspring)
+    }
+
+    fn after_sort(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        (**self).after_sort(population)
+    }
+
+    fn after_reinsert(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        (**self).after_reinsert(population)
+    }
+}
+
+impl<G, F, O> Observer<G, F> for Box<O>
+    where
+        G: Genotype,
+        F: Fitness,
+        O: Observer<G, F> + ?Sized,
+{
+    fn before_select(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        (**self).before_select(population)
+    }
+
+    fn after_select(&mut self, population: &SortedPopulation<G, F>, parents: &[Vec<&G>]) -> Result<()> {
+        (**self).after_select(population, parents)
+    }
+
+    fn after_crossover(&mut self, population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        (**self).after_crossover(population, offspring)
+    }
+
+    fn after_mutate(&mut self, population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        (**self).after_mutate(population, offspring)
+    }
+
+    fn after_breed(&mut self, population: &SortedPopulation<G, F>, offspring: &[Offspring<G>]) -> Result<()> {
+        (**self).after_breed(population, offspring)
+    }
+
+    fn after_sort(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        (**self).after_sort(population)
+    }
+
+    fn after_reinsert(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        (**self).after_reinsert(population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use crate::error::*;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// Logs every hook as `name:hook`, failing in `fail_at` if set.
+    struct Recording {
+        name: &'static str,
+        log: Log,
+        fail_at: Option<&'static str>,
+    }
+
+    impl Recording {
+        fn new(name: &'static str, log: &Log) -> Self {
+            Self { name, log: log.clone(), fail_at: None }
+        }
+
+        fn record(&mut self, hook: &'static str) -> Result<()> {
+            self.log.borrow_mut().push(format!("{}:{}", self.name, hook));
+            match self.fail_at {
+                Some(fail_at) if fail_at == hook => Err(Error::Genetic(format!("{} failed", hook))),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl Observer<Vec<i64>, i64> for Recording {
+        fn before_select(&mut self, _population: &SortedPopulation<Vec<i64>, i64>) -> Result<()> {
+            self.record("before_select")
+        }
+
+        fn after_select(&mut self, _population: &SortedPopulation<Vec<i64>, i64>, _parents: &[Vec<&Vec<i64>>]) -> Result<()> {
+            self.record("after_select")
+        }
+
+        fn after_crossover(&mut self, _population: &SortedPopulation<Vec<i64>, i64>, _offspring: &[Vec<i64>]) -> Result<()> {
+            self.record("after_crossover")
+        }
+
+        fn after_mutate(&mut self, _population: &SortedPopulation<Vec<i64>, i64>, _offspring: &[Vec<i64>]) -> Result<()> {
+            self.record("after_mutate")
+        }
+
+        fn after_breed(&mut self, _population: &SortedPopulation<Vec<i64>, i64>, _offspring: &[Offspring<Vec<i64>>]) -> Result<()> {
+            self.record("after_breed")
+        }
+
+        fn after_sort(&mut self, _population: &SortedPopulation<Vec<i64>, i64>) -> Result<()> {
+            self.record("after_sort")
+        }
+
+        fn after_reinsert(&mut self, _population: &SortedPopulation<Vec<i64>, i64>) -> Result<()> {
+            self.record("after_reinsert")
+        }
+    }
+
+    fn advance<O: Observer<Vec<i64>, i64>>(observer: O) -> Result<()> {
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(|p: &Vec<i64>| Ok(p.iter().sum::<i64>())).use_existing_fitness())
+            .with_select(TournamentSelectionBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(0)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(RandomResetMutationBuilder::default()
+                .with_bounds(Bounds::Uniform(0, 1))
+                .with_rng(ChaCha8Rng::seed_from_u64(2))
+                .build()
+                .unwrap())
+            .with_reinsert(ElitistReinserter)
+            .with_observer(observer);
+        let mut population = builder.create_population(vec![vec![0; 8]; 10]).unwrap();
+        population.next_generation();
+        builder.build().advance(population).map(|_| ())
+    }
+
+    const HOOKS: [&str; 7] = ["before_select", "after_select", "after_crossover", "after_mutate", "after_breed", "after_sort", "after_reinsert"];
+
+    #[test]
+    fn test_hook_order() {
+        let log = Log::default();
+        advance(Recording::new("a", &log)).unwrap();
+        let expected: Vec<String> = HOOKS.iter().map(|hook| format!("a:{}", hook)).collect();
+        assert_eq!(*log.borrow(), expected);
+    }
+
+    #[test]
+    fn test_observers_are_called_in_order_added() {
+        let log = Log::default();
+        let mut second = Recording::new("b", &log);
+        // `with_observer` nests the observers into pairs.
+        advance((Recording::new("a", &log), &mut second)).unwrap();
+        let expected: Vec<String> = HOOKS
+            .iter()
+            .flat_map(|hook| [format!("a:{}", hook), format!("b:{}", hook)])
+            .collect();
+        assert_eq!(*log.borrow(), expected);
+    }
+
+    #[test]
+    fn test_hook_error_stops_advance() {
+        let log = Log::default();
+        let mut failing = Recording::new("a", &log);
+        failing.fail_at = Some("after_crossover");
+        let result = advance(Box::new((failing, Recording::new("b", &log))));
+
+        assert!(matches!(result, Err(Error::Genetic(message)) if message == "after_crossover failed"));
+        assert_eq!(*log.borrow(), vec!["a:before_select", "b:before_select", "a:after_select", "b:after_select", "a:after_crossover"]);
+    }
+}
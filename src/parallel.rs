@@ -0,0 +1,160 @@
+//! Opt-in parallel growth and evaluation on a rayon thread pool.
+//!
+//! [`ParallelIncubator`] grows phenotypes in parallel and [`ParallelFitness`]
+//! evaluates a [`IndependentFitnessFunction`] in parallel. Both preserve the order
+//! of the population, so results are the same as with the sequential versions.
+//! Without an explicit pool they run on rayon's global pool.
+
+use std::sync::Arc;
+
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::types::*;
+use crate::error::*;
+
+/// A fitness function where the fitness of a phenotype does not depend on the rest of the population.
+pub trait IndependentFitnessFunction: FitnessFunction {
+    fn evaluate_one(&self, phenotype: &Self::Phenotype, fitness: Option<&Self::Fitness>) -> Result<Self::Fitness>;
+}
+
+fn build_pool(num_threads: usize) -> Result<Arc<ThreadPool>> {
+    ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map(Arc::new)
+        .map_err(|err| Error::Genetic(err.to_string()))
+}
+
+fn install<R, OP>(pool: &Option<Arc<ThreadPool>>, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+{
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+pub struct ParallelIncubator<I> {
+    incubator: I,
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl<I> ParallelIncubator<I> {
+    pub fn new(incubator: I) -> Self {
+        Self { incubator, pool: None }
+    }
+
+    /// Run on a dedicated pool of `num_threads` threads.
+    pub fn with_num_threads(self, num_threads: usize) -> Result<Self> {
+        Ok(self.with_pool(build_pool(num_threads)?))
+    }
+
+    /// Run on an existing pool, e.g. one shared with [`ParallelFitness`].
+    pub fn with_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+}
+
+impl<I> Incubator for ParallelIncubator<I>
+    where
+        I: Incubator + Sync,
+        I::Genotype: Sync,
+        I::Phenotype: Send,
+{
+    type Genotype = I::Genotype;
+    type Phenotype = I::Phenotype;
+
+    fn grow(&self, genome: &Self::Genotype) -> Result<Self::Phenotype> {
+        self.incubator.grow(genome)
+    }
+
+    fn grow_all(&self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Phenotype>> {
+        install(&self.pool, || {
+            genomes
+                .par_iter()
+                .map(|genome| self.incubator.grow(genome))
+                .collect()
+        })
+    }
+}
+
+pub struct ParallelFitness<F> {
+    fitness_function: F,
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl<F> ParallelFitness<F> {
+    pub fn new(fitness_function: F) -> Self {
+        Self { fitness_function, pool: None }
+    }
+
+    /// Run on a dedicated pool of `num_threads` threads.
+    pub fn with_num_threads(self, num_threads: usize) -> Result<Self> {
+        Ok(self.with_pool(build_pool(num_threads)?))
+    }
+
+    /// Run on an existing pool, e.g. one shared with [`ParallelIncubator`].
+    pub fn with_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+}
+
+impl<F> FitnessFunction for ParallelFitness<F>
+    where
+        F: IndependentFitnessFunction + Sync,
+        F::Phenotype: Sync,
+        F::Fitness: Send + Sync,
+{
+    type Phenotype = F::Phenotype;
+    type Fitness = F::Fitness;
+
+    fn evaluate(&self, phenotypes_with_fitnesses: &[(&Self::Phenotype, Option<&Self::Fitness>)]) -> Result<Vec<Self::Fitness>> {
+        install(&self.pool, || {
+            phenotypes_with_fitnesses
+                .par_iter()
+                .map(|(phenotype, fitness)| self.fitness_function.evaluate_one(phenotype, *fitness))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::population::*;
+    use crate::fitness::*;
+    use super::*;
+
+    #[test]
+    fn test_parallel_sort_matches_sequential() {
+        let genomes: Vec<Vec<i64>> = (0..200)
+            .map(|i| vec![(i * 7919) % 101, i % 13, -(i % 5)])
+            .collect();
+        let fitness = |phenotype: &Vec<i64>| Ok(phenotype.iter().sum::<i64>());
+
+        let sequential = UnsortedPopulation::default()
+            .add_children(genomes.clone())
+            .sort(
+                &IdentityIncubator::default(),
+                &SimpleFitness::new(fitness).recalculate_fitness(),
+            )
+            .unwrap();
+
+        let pool = build_pool(4).unwrap();
+        let parallel = UnsortedPopulation::default()
+            .add_children(genomes)
+            .sort(
+                &ParallelIncubator::new(IdentityIncubator::default()).with_pool(pool.clone()),
+                &ParallelFitness::new(SimpleFitness::new(fitness).recalculate_fitness()).with_pool(pool),
+            )
+            .unwrap();
+
+        let sequential: Vec<_> = sequential.individuals.iter().map(|i| (&i.genome, i.fitness)).collect();
+        let parallel: Vec<_> = parallel.individuals.iter().map(|i| (&i.genome, i.fitness)).collect();
+        assert_eq!(sequential, parallel);
+    }
+}
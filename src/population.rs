@@ -1,13 +1,18 @@
 use std::ops::Index;
 use std::marker::PhantomData;
 
+use serde::{Deserialize, Serialize};
+
 use crate::types::*;
 use crate::individual::*;
+use crate::fitness::Distance;
+use crate::dedup::Deduplicate;
 use crate::error::*;
 
 pub struct Sorted;
 pub struct Unsorted;
 
+#[derive(Serialize, Deserialize)]
 pub struct Population<G: Genotype, F: Fitness, S> {
     pub(crate) individuals: Vec<Individual<G, F>>,
     pub(crate) generation: u64,
@@ -23,14 +28,29 @@ impl<G, F, S> Population<G, F, S>
         G: Genotype,
         F: Fitness,
 {
-    pub fn add_children(mut self, genomes: Vec<G>) -> UnsortedPopulation<G, F> {
-        let num_children = genomes.len();
-        self.individuals.extend(
+    pub fn add_children(self, genomes: Vec<G>) -> UnsortedPopulation<G, F> {
+        let generation = self.generation;
+        self.add_individuals(
             genomes
                 .into_iter()
-                .map(|genome| Individual { generation: self.generation, genome, fitness: None })
-        );
-        
+                .map(|genome| Individual { generation, genome, fitness: None, id: None })
+                .collect()
+        )
+    }
+
+    /// Like [`add_children`](Population::add_children), but children duplicating an individual
+    /// of the population or an earlier child are replaced or rejected as `deduplicate` says.
+    pub fn add_unique_children<D: Distance<G>>(self, genomes: Vec<G>, deduplicate: &mut Deduplicate<G, D>) -> Result<UnsortedPopulation<G, F>> {
+        let existing: Vec<&G> = self.individuals.iter().map(|individual| &individual.genome).collect();
+        let genomes = deduplicate.unique(&existing, genomes)?;
+        Ok(self.add_children(genomes))
+    }
+
+    /// Add `individuals` to the population, counting them as children of this generation.
+    pub fn add_individuals(mut self, individuals: Vec<Individual<G, F>>) -> UnsortedPopulation<G, F> {
+        let num_children = individuals.len();
+        self.individuals.extend(individuals);
+
         UnsortedPopulation {
             individuals: self.individuals,
             generation: self.generation,
@@ -104,10 +124,11 @@ impl<G, F> UnsortedPopulation<G, F>
             P: Phenotype,
             FF: FitnessFunction<Fitness = F, Phenotype = P>
     {
-        let phenotypes = self.individuals
+        let genomes: Vec<_> = self.individuals
             .iter()
-            .map(|individual| incubator.grow(&individual.genome))
-            .collect::<Result<Vec<_>>>()?;
+            .map(|individual| &individual.genome)
+            .collect();
+        let phenotypes = incubator.grow_all(&genomes)?;
 
         let phenotypes_with_fitnesses: Vec<_> = phenotypes
             .iter()
@@ -158,12 +179,29 @@ impl<G, F> SortedPopulation<G, F>
     pub fn best(&self) -> &Individual<G, F> {
         &self.individuals[0]
     }
+
+    /// Replace the individual at `index` with an evaluated `individual`, keeping the
+    /// population sorted without comparing the other individuals among each other.
+    /// Returns the position `individual` was inserted at.
+    pub fn replace(&mut self, index: usize, individual: Individual<G, F>) -> usize {
+        self.individuals.remove(index);
+        self.insert(individual)
+    }
+
+    /// Insert an evaluated `individual` at its sorted position, after any equally fit ones.
+    /// Returns the position it was inserted at.
+    pub fn insert(&mut self, individual: Individual<G, F>) -> usize {
+        let position = self.individuals.partition_point(|member| member.fitness >= individual.fitness);
+        self.individuals.insert(position, individual);
+        position
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::population;
 
+    use crate::fitnesses::Minimize;
     use super::*;
 
     impl Genotype for usize {}
@@ -226,4 +264,18 @@ mod tests {
 
         assert_eq!(individuals, expected);
     }
+
+    #[test]
+    fn test_population_sort_minimize() {
+        let fitness_function = crate::fitness::SimpleFitness::new(|phenotype: &Vec<i64>| Ok(Minimize(phenotype[0])))
+            .recalculate_fitness();
+
+        let population = UnsortedPopulation::default()
+            .add_children(vec![vec![3], vec![-2], vec![7]])
+            .sort(&IdentityIncubator::default(), &fitness_function)
+            .unwrap();
+
+        assert_eq!(population.best().fitness, Some(Minimize(-2)));
+        assert_eq!(population.individuals[2].genome, vec![7]);
+    }
 }
\ No newline at end of file
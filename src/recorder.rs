@@ -0,0 +1,365 @@
+//! Recording of runs into a SQLite database.
+//!
+//! [`SqliteRecorder`] is an [`Observer`] that stores every generation produced by
+//! `advance` (and any population passed to [`SqliteRecorder::record`]) in a local
+//! SQLite file, along with every evaluated child, including those that did not survive
+//! reinsertion. Genomes and fitnesses are stored serialized with bincode.
+//!
+//! The schema is:
+//!
+//! ```sql
+//! runs(id, started_at, metadata)
+//! individuals(run_id, generation, position, birth_generation, genome, fitness, individual_id)
+//! children(run_id, generation, position, birth_generation, genome, fitness, individual_id)
+//! ```
+//!
+//! `position` is the index of the individual in its sorted generation, so the
+//! best individual of every generation is the one at position 0. `children` holds the
+//! children born in every generation, best first. `individual_id` is the lineage
+//! [`id`](crate::individual::Individual::id) of the individual, if it has one.
+
+use std::marker::PhantomData;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::individual::*;
+use crate::population::*;
+use crate::observer::*;
+use crate::error::*;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        started_at INTEGER NOT NULL,
+        metadata TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS individuals (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        generation INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        birth_generation INTEGER NOT NULL,
+        genome BLOB NOT NULL,
+        fitness BLOB,
+        individual_id INTEGER,
+        PRIMARY KEY (run_id, generation, position)
+    );
+    CREATE TABLE IF NOT EXISTS children (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        generation INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        birth_generation INTEGER NOT NULL,
+        genome BLOB NOT NULL,
+        fitness BLOB,
+        individual_id INTEGER,
+        PRIMARY KEY (run_id, generation, position)
+    );
+";
+
+/// A run stored in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    pub id: i64,
+    /// Seconds since the Unix epoch.
+    pub started_at: i64,
+    /// Run metadata, as JSON.
+    pub metadata: String,
+}
+
+pub struct SqliteRecorder<G, F> {
+    connection: Connection,
+    run_id: i64,
+    _phantom: PhantomData<(G, F)>,
+}
+
+impl<G, F> SqliteRecorder<G, F> {
+    /// Open (or create) the database at `path` and start a new run described by `metadata`.
+    pub fn create<P: AsRef<Path>, M: Serialize>(path: P, metadata: &M) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?, metadata)
+    }
+
+    /// Start a new run in an already opened database.
+    pub fn with_connection<M: Serialize>(connection: Connection, metadata: &M) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        connection.execute(
+            "INSERT INTO runs (started_at, metadata) VALUES (?1, ?2)",
+            params![started_at, serde_json::to_string(metadata)?],
+        )?;
+        let run_id = connection.last_insert_rowid();
+
+        Ok(Self { connection, run_id, _phantom: PhantomData })
+    }
+
+    /// Open an existing run for reading.
+    pub fn open_run<P: AsRef<Path>>(path: P, run_id: i64) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        let exists = connection
+            .query_row("SELECT id FROM runs WHERE id = ?1", [run_id], |row| row.get::<_, i64>(0))
+            .optional()?;
+        if exists.is_none() {
+            return Err(Error::Genetic(format!("Run {} does not exist", run_id)));
+        }
+
+        Ok(Self { connection, run_id, _phantom: PhantomData })
+    }
+
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// All runs stored in the database, oldest first.
+    pub fn runs(&self) -> Result<Vec<RunRecord>> {
+        let mut statement = self.connection.prepare("SELECT id, started_at, metadata FROM runs ORDER BY id")?;
+        let runs = statement
+            .query_map([], |row| Ok(RunRecord { id: row.get(0)?, started_at: row.get(1)?, metadata: row.get(2)? }))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(runs)
+    }
+
+    /// The generations recorded for this run, in ascending order.
+    pub fn generations(&self) -> Result<Vec<u64>> {
+        let mut statement = self.connection.prepare(
+            "SELECT DISTINCT generation FROM individuals WHERE run_id = ?1 ORDER BY generation"
+        )?;
+        let generations = statement
+            .query_map([self.run_id], |row| row.get::<_, i64>(0))?
+            .map(|generation| generation.map(|generation| generation as u64))
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(generations)
+    }
+}
+
+impl<G, F> SqliteRecorder<G, F>
+    where
+        G: Genotype + Serialize,
+        F: Fitness + Serialize,
+{
+    /// Store a sorted population as the generation it currently is in, replacing any
+    /// population recorded before as that generation.
+    pub fn record(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.store("individuals", population.generation, population.individuals.iter())
+    }
+
+    /// Store the children born in the current generation of a sorted population, replacing
+    /// any children recorded before for that generation.
+    pub fn record_children(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        let children = population.individuals
+            .iter()
+            .filter(|individual| individual.generation == population.generation);
+        self.store("children", population.generation, children)
+    }
+
+    fn store<'a, T>(&mut self, table: &str, generation: u64, individuals: T) -> Result<()>
+        where
+            T: Iterator<Item = &'a Individual<G, F>>,
+            G: 'a,
+            F: 'a,
+    {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            &format!("DELETE FROM {} WHERE run_id = ?1 AND generation = ?2", table),
+            params![self.run_id, generation as i64],
+        )?;
+        {
+            let mut statement = transaction.prepare(&format!(
+                "INSERT INTO {} (run_id, generation, position, birth_generation, genome, fitness, individual_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                table,
+            ))?;
+            for (position, individual) in individuals.enumerate() {
+                let fitness = individual.fitness
+                    .as_ref()
+                    .map(bincode::serialize)
+                    .transpose()?;
+                statement.execute(params![
+                    self.run_id,
+                    generation as i64,
+                    position as i64,
+                    individual.generation as i64,
+                    bincode::serialize(&individual.genome)?,
+                    fitness,
+                    individual.id.map(|id| id as i64),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+impl<G, F> SqliteRecorder<G, F>
+    where
+        G: Genotype + DeserializeOwned,
+        F: Fitness + DeserializeOwned,
+{
+    fn query_individuals(&self, sql: &str, generation: Option<u64>) -> Result<Vec<(u64, Individual<G, F>)>> {
+        let mut statement = self.connection.prepare(sql)?;
+        let mut rows = match generation {
+            Some(generation) => statement.query(params![self.run_id, generation as i64])?,
+            None => statement.query(params![self.run_id])?,
+        };
+
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let generation: i64 = row.get(0)?;
+            let birth_generation: i64 = row.get(1)?;
+            let genome: Vec<u8> = row.get(2)?;
+            let fitness: Option<Vec<u8>> = row.get(3)?;
+            let id: Option<i64> = row.get(4)?;
+            let individual = Individual {
+                generation: birth_generation as u64,
+                genome: bincode::deserialize(&genome)?,
+                fitness: fitness.map(|fitness| bincode::deserialize(&fitness)).transpose()?,
+                id: id.map(|id| id as u64),
+            };
+            result.push((generation as u64, individual));
+        }
+        Ok(result)
+    }
+
+    /// The best individual of every recorded generation, paired with that generation.
+    pub fn best_per_generation(&self) -> Result<Vec<(u64, Individual<G, F>)>> {
+        self.query_individuals(
+            "SELECT generation, birth_generation, genome, fitness, individual_id FROM individuals
+             WHERE run_id = ?1 AND position = 0 ORDER BY generation",
+            None,
+        )
+    }
+
+    /// Reload a recorded generation. Stored fitnesses are kept, so the population can be
+    /// re-sorted without re-evaluation by a fitness function that reuses existing fitness.
+    pub fn load_generation(&self, generation: u64) -> Result<UnsortedPopulation<G, F>> {
+        let individuals: Vec<_> = self
+            .query_individuals(
+                "SELECT generation, birth_generation, genome, fitness, individual_id FROM individuals
+                 WHERE run_id = ?1 AND generation = ?2 ORDER BY position",
+                Some(generation),
+            )?
+            .into_iter()
+            .map(|(_, individual)| individual)
+            .collect();
+
+        if individuals.is_empty() {
+            return Err(Error::Genetic(format!("Generation {} was not recorded", generation)));
+        }
+
+        Ok(UnsortedPopulation {
+            individuals,
+            generation,
+            num_children: 0,
+            sorted: PhantomData,
+        })
+    }
+
+    /// The children evaluated in `generation`, best first, whether or not they survived.
+    pub fn children(&self, generation: u64) -> Result<Vec<Individual<G, F>>> {
+        Ok(self
+            .query_individuals(
+                "SELECT generation, birth_generation, genome, fitness, individual_id FROM children
+                 WHERE run_id = ?1 AND generation = ?2 ORDER BY position",
+                Some(generation),
+            )?
+            .into_iter()
+            .map(|(_, individual)| individual)
+            .collect())
+    }
+}
+
+impl<G, F> Observer<G, F> for SqliteRecorder<G, F>
+    where
+        G: Genotype + Serialize,
+        F: Fitness + Serialize,
+{
+    fn after_sort(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.record_children(population)
+    }
+
+    fn after_reinsert(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        self.record(population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_population(generation: u64, fitnesses: Vec<i64>) -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: fitnesses
+                .into_iter()
+                .map(|fitness| Individual { generation, genome: vec![fitness, -fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_record_and_reload() {
+        let connection = Connection::open_in_memory().unwrap();
+        let mut recorder = SqliteRecorder::with_connection(connection, &("tournament", 12)).unwrap();
+
+        recorder.record(&test_population(0, vec![5, 3, 1])).unwrap();
+        recorder.after_reinsert(&test_population(1, vec![8, 5, 3])).unwrap();
+
+        assert_eq!(recorder.generations().unwrap(), vec![0, 1]);
+        assert_eq!(recorder.runs().unwrap()[0].metadata, r#"["tournament",12]"#);
+
+        let best: Vec<_> = recorder.best_per_generation()
+            .unwrap()
+            .into_iter()
+            .map(|(generation, individual)| (generation, individual.fitness))
+            .collect();
+        assert_eq!(best, vec![(0, Some(5)), (1, Some(8))]);
+
+        let population = recorder.load_generation(1).unwrap();
+        let genomes: Vec<_> = population.individuals.iter().map(|individual| individual.genome.clone()).collect();
+        assert_eq!(genomes, vec![vec![8, -8], vec![5, -5], vec![3, -3]]);
+        assert!(recorder.load_generation(2).is_err());
+    }
+
+    #[test]
+    fn test_rerecording_replaces_generation() {
+        let connection = Connection::open_in_memory().unwrap();
+        let mut recorder = SqliteRecorder::with_connection(connection, &()).unwrap();
+
+        recorder.record(&test_population(3, vec![5, 3, 1])).unwrap();
+        let mut population = test_population(3, vec![9, 4]);
+        population.individuals[0].id = Some(42);
+        recorder.record(&population).unwrap();
+
+        let population = recorder.load_generation(3).unwrap();
+        let loaded: Vec<_> = population.individuals.iter().map(|individual| (individual.fitness, individual.id)).collect();
+        assert_eq!(loaded, vec![(Some(9), Some(42)), (Some(4), None)]);
+    }
+
+    #[test]
+    fn test_records_discarded_children() {
+        let connection = Connection::open_in_memory().unwrap();
+        let mut recorder = SqliteRecorder::with_connection(connection, &()).unwrap();
+
+        let mut population = test_population(1, vec![9, 5, 3, 2]);
+        population.individuals[1].generation = 0;
+        population.individuals[2].generation = 0;
+        recorder.after_sort(&population).unwrap();
+        recorder.after_reinsert(&test_population(1, vec![9, 5, 3])).unwrap();
+
+        let children: Vec<_> = recorder.children(1).unwrap().into_iter().map(|child| child.fitness).collect();
+        assert_eq!(children, vec![Some(9), Some(2)]);
+        assert!(recorder.children(0).unwrap().is_empty());
+    }
+}
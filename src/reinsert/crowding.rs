@@ -0,0 +1,439 @@
+//! Crowding: every child competes with a similar individual for its place in the population.
+//!
+//! Both operators are [`ReinsertOperator`]s, an alternative to truncation for
+//! [`GeneticAlgorithm::advance`](crate::ga::GeneticAlgorithm::advance): the children compete
+//! one after the other for the places of the previous generation, so the population keeps its
+//! size. [`Crowding`] learns the parents of every child from
+//! [`ReinsertOperator::reinsert_with_parents`]. They are also [`ReplaceOperator`]s for
+//! [`GeneticAlgorithm::run_steady_state`](crate::ga::GeneticAlgorithm::run_steady_state).
+//! Similarity is measured on genomes with a [`Distance`].
+
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand::seq::index::sample;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::population::*;
+use crate::individual::*;
+use crate::types::*;
+use crate::error::*;
+use crate::fitness::Distance;
+use crate::checkpoint::Stateful;
+use super::{split_generations, with_survivors};
+
+/// How a child competes with the individual it may replace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum Competition {
+    /// The child replaces the individual if it is strictly better.
+    #[default]
+    Deterministic,
+    /// The child replaces the individual with probability `w_child / (w_child + w_individual)`,
+    /// using the [weights](ScalarFitness::to_weight) of both. Negative fitnesses are an error.
+    Probabilistic,
+}
+
+impl Competition {
+    /// Whether `child` wins against `incumbent`.
+    fn child_wins<F: ScalarFitness, R: Rng>(&self, child: &F, incumbent: &F, rng: &mut R) -> Result<bool> {
+        match self {
+            Competition::Deterministic => Ok(child > incumbent),
+            Competition::Probabilistic => {
+                let (child, incumbent) = (child.to_weight()?, incumbent.to_weight()?);
+                let total = child + incumbent;
+                let probability = if child == f64::INFINITY || incumbent == f64::INFINITY {
+                    // The ratio would be NaN: an infinite fitness wins outright, two of them tie.
+                    match (child == f64::INFINITY, incumbent == f64::INFINITY) {
+                        (true, true) => 0.5,
+                        (true, false) => 1.0,
+                        _ => 0.0,
+                    }
+                } else if total > 0.0 {
+                    child / total
+                } else {
+                    0.5
+                };
+                Ok(rng.gen_bool(probability.clamp(0.0, 1.0)))
+            },
+        }
+    }
+}
+
+/// The most similar of the `candidates` among `individuals` to `child`, and whether `child`
+/// replaces it.
+fn compete<G, F, D, R>(individuals: &[Individual<G, F>], child: &Individual<G, F>, candidates: &[usize], distance: &D, competition: Competition, rng: &mut R) -> Result<Option<usize>>
+    where
+        G: Genotype,
+        F: ScalarFitness,
+        D: Distance<G>,
+        R: Rng,
+{
+    let closest = candidates
+        .iter()
+        .copied()
+        .map(|i| (i, distance.distance(&child.genome, &individuals[i].genome)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i);
+
+    let closest = match closest {
+        Some(closest) => closest,
+        None => return Ok(None),
+    };
+    let (child_fitness, incumbent_fitness) = match (&child.fitness, &individuals[closest].fitness) {
+        (Some(child), Some(incumbent)) => (child, incumbent),
+        _ => return Err(Error::Genetic("Crowding requires evaluated individuals".into())),
+    };
+    Ok(competition.child_wins(child_fitness, incumbent_fitness, rng)?.then_some(closest))
+}
+
+/// Every child of the current generation competes, best first, for one of the places of the
+/// previous generation, chosen by `place` among the individuals that hold them.
+fn crowd<G, F, P>(mut population: SortedPopulation<G, F>, mut place: P) -> Result<UnsortedPopulation<G, F>>
+    where
+        G: Genotype,
+        F: Fitness,
+        P: FnMut(&[Individual<G, F>], &Individual<G, F>) -> Result<Option<usize>>,
+{
+    let (mut places, children) = split_generations(&mut population);
+    for child in children {
+        if let Some(index) = place(&places, &child)? {
+            places[index] = child;
+        }
+    }
+    Ok(with_survivors(&population, places))
+}
+
+/// Deterministic or probabilistic crowding: every child competes with its most similar parent.
+///
+/// In generational mode, a parent the child competes with must still hold its place, so
+/// siblings compete with different parents, and children without parents are discarded.
+pub struct Crowding<D, R: Rng> {
+    pub distance: D,
+    pub competition: Competition,
+    rng: R,
+}
+
+impl<D, R: Rng> Crowding<D, R> {
+    /// Deterministic crowding, the rng being used by the probabilistic variant only.
+    pub fn new(distance: D, rng: R) -> Self {
+        Self { distance, competition: Competition::default(), rng }
+    }
+
+    pub fn with_competition(mut self, competition: Competition) -> Self {
+        self.competition = competition;
+        self
+    }
+}
+
+impl<G, F, D, R> ReplaceOperator<G, F> for Crowding<D, R>
+    where
+        G: Genotype,
+        F: ScalarFitness,
+        D: Distance<G>,
+        R: Rng,
+{
+    fn replace(&mut self, population: &SortedPopulation<G, F>, child: &Individual<G, F>, parents: &[usize]) -> Result<Option<usize>> {
+        compete(&population.individuals, child, parents, &self.distance, self.competition, &mut self.rng)
+    }
+}
+
+impl<G, F, D, R> ReinsertOperator<G, F> for Crowding<D, R>
+    where
+        G: Genotype,
+        F: ScalarFitness,
+        D: Distance<G>,
+        R: Rng,
+{
+    fn reinsert(&mut self, _population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        Err(Error::Genetic("Crowding needs the parents of the children, see ReinsertOperator::reinsert_with_parents".into()))
+    }
+
+    fn reinsert_with_parents(&mut self, population: SortedPopulation<G, F>, parents: &HashMap<u64, Vec<u64>>) -> Result<UnsortedPopulation<G, F>> {
+        crowd(population, |places, child| {
+            let parents = child.id.and_then(|id| parents.get(&id)).map_or(&[][..], Vec::as_slice);
+            let candidates: Vec<usize> = places
+                .iter()
+                .enumerate()
+                .filter(|(_, individual)| individual.id.is_some_and(|id| parents.contains(&id)))
+                .map(|(index, _)| index)
+                .collect();
+            compete(places, child, &candidates, &self.distance, self.competition, &mut self.rng)
+        })
+    }
+}
+
+/// Restricted tournament selection: every child competes with the most similar individual of
+/// `window_size` individuals drawn at random from the population.
+pub struct RestrictedTournament<D, R: Rng> {
+    pub distance: D,
+    pub window_size: usize,
+    pub competition: Competition,
+    rng: R,
+}
+
+impl<D, R: Rng> RestrictedTournament<D, R> {
+    pub fn new(distance: D, window_size: usize, rng: R) -> Self {
+        Self { distance, window_size, competition: Competition::default(), rng }
+    }
+
+    pub fn with_competition(mut self, competition: Competition) -> Self {
+        self.competition = competition;
+        self
+    }
+}
+
+impl<G, F, D, R> ReplaceOperator<G, F> for RestrictedTournament<D, R>
+    where
+        G: Genotype,
+        F: ScalarFitness,
+        D: Distance<G>,
+        R: Rng,
+{
+    fn replace(&mut self, population: &SortedPopulation<G, F>, child: &Individual<G, F>, _parents: &[usize]) -> Result<Option<usize>> {
+        let len = population.individuals.len();
+        let window = sample(&mut self.rng, len, self.window_size.min(len)).into_vec();
+        compete(&population.individuals, child, &window, &self.distance, self.competition, &mut self.rng)
+    }
+}
+
+impl<G, F, D, R> ReinsertOperator<G, F> for RestrictedTournament<D, R>
+    where
+        G: Genotype,
+        F: ScalarFitness,
+        D: Distance<G>,
+        R: Rng,
+{
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        crowd(population, |places, child| {
+            let window = sample(&mut self.rng, places.len(), self.window_size.min(places.len())).into_vec();
+            compete(places, child, &window, &self.distance, self.competition, &mut self.rng)
+        })
+    }
+}
+
+impl<D, R> Stateful for Crowding<D, R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<D, R> Stateful for RestrictedTournament<D, R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use super::*;
+
+    fn position() -> impl Fn(&Vec<i64>, &Vec<i64>) -> f64 {
+        |a, b| (a[0] - b[0]).abs() as f64
+    }
+
+    // Genomes are (position, fitness).
+    fn test_population(individuals: Vec<(i64, i64)>) -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: individuals
+                .into_iter()
+                .map(|(position, fitness)| Individual { generation: 0, genome: vec![position, fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 1,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    fn child(position: i64, fitness: i64) -> Individual<Vec<i64>, i64> {
+        Individual { generation: 1, genome: vec![position, fitness], fitness: Some(fitness), id: None }
+    }
+
+    #[test]
+    fn test_deterministic_crowding_replaces_closest_parent() {
+        let population = test_population(vec![(0, 9), (10, 5), (20, 3)]);
+        let mut crowding = Crowding::new(position(), ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(crowding.replace(&population, &child(12, 6), &[0, 1]).unwrap(), Some(1));
+        // The closest parent is better, so the child is discarded even though it beats the other one.
+        assert_eq!(crowding.replace(&population, &child(1, 6), &[0, 1]).unwrap(), None);
+        assert_eq!(crowding.replace(&population, &child(1, 6), &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_probabilistic_crowding() {
+        let population = test_population(vec![(0, 3), (10, 1)]);
+        let mut crowding = Crowding::new(position(), ChaCha8Rng::seed_from_u64(0))
+            .with_competition(Competition::Probabilistic);
+
+        // The child wins with probability 1 / (1 + 3).
+        let wins = (0..1000)
+            .filter(|_| crowding.replace(&population, &child(1, 1), &[0, 1]).unwrap().is_some())
+            .count();
+        assert!((200..300).contains(&wins));
+    }
+
+    #[test]
+    fn test_probabilistic_competition_with_minimized_fitness() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let competition = Competition::Probabilistic;
+        // The weights are 1 / (1 + 1) and 1 / (1 + 3), so the child wins with probability 2 / 3.
+        let wins = (0..1000)
+            .filter(|_| competition.child_wins(&Minimize(1), &Minimize(3), &mut rng).unwrap())
+            .count();
+        assert!((620..720).contains(&wins));
+    }
+
+    #[test]
+    fn test_probabilistic_competition_with_infinite_fitness() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (infinite, finite) = (NotNan::new(f64::INFINITY).unwrap(), NotNan::new(1.0).unwrap());
+        let competition = Competition::Probabilistic;
+        assert!(competition.child_wins(&infinite, &finite, &mut rng).unwrap());
+        assert!(!competition.child_wins(&finite, &infinite, &mut rng).unwrap());
+        let wins = (0..1000).filter(|_| competition.child_wins(&infinite, &infinite, &mut rng).unwrap()).count();
+        assert!((400..600).contains(&wins));
+    }
+
+    #[test]
+    fn test_probabilistic_competition_rejects_negative_fitness() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let (negative, finite) = (NotNan::new(f64::NEG_INFINITY).unwrap(), NotNan::new(1.0).unwrap());
+        let competition = Competition::Probabilistic;
+        assert!(competition.child_wins(&negative, &finite, &mut rng).is_err());
+        assert!(competition.child_wins(&finite, &negative, &mut rng).is_err());
+        assert!(competition.child_wins(&-1, &1, &mut rng).is_err());
+    }
+
+    // Individuals are (id, generation, position, fitness), best first.
+    fn generational_population(individuals: Vec<(u64, u64, i64, i64)>) -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: individuals
+                .into_iter()
+                .map(|(id, generation, position, fitness)| Individual { generation, genome: vec![position, fitness], fitness: Some(fitness), id: Some(id) })
+                .collect(),
+            generation: 1,
+            num_children: 4,
+            sorted: PhantomData,
+        }
+    }
+
+    fn positions<S>(population: &Population<Vec<i64>, i64, S>) -> Vec<i64> {
+        population.individuals.iter().map(|individual| individual.genome[0]).collect()
+    }
+
+    #[test]
+    fn test_generational_crowding() {
+        let population = generational_population(vec![
+            (0, 0, 0, 9), (13, 1, 5, 8), (11, 1, 11, 7), (10, 1, 12, 6),
+            (1, 0, 10, 5), (2, 0, 20, 3), (12, 1, 29, 2), (3, 0, 30, 1),
+        ]);
+        let parents: HashMap<u64, Vec<u64>> = [(10, vec![0, 1]), (11, vec![0, 1]), (12, vec![2, 3])].into_iter().collect();
+        let mut crowding = Crowding::new(position(), ChaCha8Rng::seed_from_u64(0));
+
+        // 11 takes the place of its closest parent 1, so its sibling 10 can only compete with
+        // the better parent 0. 13 has no parents and is discarded.
+        let survivors = crowding.reinsert_with_parents(population, &parents).unwrap();
+        assert_eq!(positions(&survivors), vec![0, 11, 20, 29]);
+        assert_eq!(survivors.get_num_children(), 4);
+
+        let population = generational_population(vec![(0, 0, 0, 9), (10, 1, 1, 10)]);
+        assert!(crowding.reinsert(population).is_err());
+    }
+
+    #[test]
+    fn test_generational_restricted_tournament() {
+        let population = generational_population(vec![
+            (0, 0, 0, 9), (10, 1, 19, 4), (1, 0, 10, 5), (2, 0, 20, 3), (11, 1, 29, 2), (3, 0, 30, 1),
+        ]);
+        let mut tournament = RestrictedTournament::new(position(), 4, ChaCha8Rng::seed_from_u64(0));
+        let survivors = tournament.reinsert(population).unwrap();
+        assert_eq!(positions(&survivors), vec![0, 10, 19, 29]);
+    }
+
+    #[test]
+    fn test_restricted_tournament_keeps_niches() {
+        let population = test_population(vec![(0, 9), (10, 5), (20, 3), (30, 1)]);
+        let mut tournament = RestrictedTournament::new(position(), 4, ChaCha8Rng::seed_from_u64(0));
+        assert_eq!(tournament.replace(&population, &child(29, 2), &[]).unwrap(), Some(3));
+        assert_eq!(tournament.replace(&population, &child(19, 2), &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_crowding_run() {
+        struct Shift(ChaCha8Rng);
+
+        impl MutateOperator for Shift {
+            type Genotype = Vec<i64>;
+
+            fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+                genome[0] = (genome[0] + self.0.gen_range(-3..=3)).clamp(0, 100);
+                Ok(())
+            }
+        }
+
+        // Two peaks of the same height, at 0 and 100.
+        let fitness = |p: &Vec<i64>| Ok(100 - p[0].min(100 - p[0]));
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(fitness).use_existing_fitness())
+            .with_select(RandomSelectionBuilder::default().with_num_children(10).with_rng(ChaCha8Rng::seed_from_u64(0)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(Shift(ChaCha8Rng::seed_from_u64(2)))
+            .with_reinsert(Crowding::new(position(), ChaCha8Rng::seed_from_u64(3)));
+
+        let population = builder.create_population((0..20).map(|i| vec![i * 5]).collect()).unwrap();
+        let summary = builder.build().run_steady_state(population, MaxGenerations(100)).unwrap();
+
+        let positions: Vec<i64> = summary.population.individuals.iter().map(|individual| individual.genome[0]).collect();
+        assert_eq!(positions.len(), 20);
+        assert!(positions.iter().any(|x| *x < 10));
+        assert!(positions.iter().any(|x| *x > 90));
+    }
+
+    #[test]
+    fn test_generational_crowding_run() {
+        // Two peaks of the same height, at 0 and 100.
+        let fitness = |p: &Vec<i64>| Ok(100 - p[0].min(100 - p[0]));
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(SimpleFitness::new(fitness).use_existing_fitness())
+            .with_select(RandomSelectionBuilder::default().with_num_children(10).with_rng(ChaCha8Rng::seed_from_u64(0)).build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(RandomResetMutationBuilder::default()
+                .with_rate(MutationRate::PerGenome(0.5))
+                .with_bounds(Bounds::Uniform(0, 100))
+                .with_rng(ChaCha8Rng::seed_from_u64(2))
+                .build()
+                .unwrap())
+            .with_reinsert(Crowding::new(position(), ChaCha8Rng::seed_from_u64(3)));
+
+        let population = builder.create_population((0..20).map(|i| vec![i * 5]).collect()).unwrap();
+        let summary = builder.build().run(population, MaxGenerations(50)).unwrap();
+
+        let positions = positions(&summary.population);
+        assert_eq!(positions.len(), 20);
+        assert!(positions.iter().any(|x| *x < 10));
+        assert!(positions.iter().any(|x| *x > 90));
+    }
+}
@@ -1,17 +1,21 @@
 use crate::population::*;
 use crate::types::*;
 use crate::error::*;
+use crate::checkpoint::Stateful;
 
 pub struct ElitistReinserter;
 
-impl ReinsertOperator for ElitistReinserter {
-    fn reinsert<G, F>(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>>
-            where 
-                G: Genotype,
-                F: Fitness 
-    {
+impl<G: Genotype, F: Fitness> ReinsertOperator<G, F> for ElitistReinserter {
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
         let pop_size = population.previous_generation_size();
         let result = population.truncate(pop_size);
         Ok(result)
     }
 }
+
+impl Stateful for ElitistReinserter {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
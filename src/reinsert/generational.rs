@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::population::*;
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::{split_generations, with_survivors};
+
+/// Generational replacement: the children replace the previous generation, except for its
+/// best `elites` individuals, which survive.
+///
+/// The population keeps its size, so there must be enough children to fill it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GenerationalReinserter {
+    pub elites: usize,
+}
+
+impl GenerationalReinserter {
+    pub fn with_elites(mut self, elites: usize) -> Self {
+        self.elites = elites;
+        self
+    }
+}
+
+impl<G: Genotype, F: Fitness> ReinsertOperator<G, F> for GenerationalReinserter {
+    fn reinsert(&mut self, mut population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        let pop_size = population.previous_generation_size();
+        let elites = self.elites.min(pop_size);
+        let (parents, children) = split_generations(&mut population);
+        if children.len() < pop_size - elites {
+            return Err(Error::Genetic(format!(
+                "Generational reinsertion with {} elites needs at least {} children, got {}",
+                elites, pop_size - elites, children.len()
+            )));
+        }
+
+        let survivors = parents
+            .into_iter()
+            .take(elites)
+            .chain(children.into_iter().take(pop_size - elites))
+            .collect();
+        Ok(with_survivors(&population, survivors))
+    }
+}
+
+impl Stateful for GenerationalReinserter {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use crate::individual::*;
+    use super::*;
+
+    #[test]
+    fn test_generational_keeps_elites() {
+        // Parents have odd fitnesses and children even ones.
+        let population = || SortedPopulation {
+            individuals: (0..10)
+                .rev()
+                .map(|fitness: i64| Individual { generation: (1 - fitness % 2) as u64, genome: vec![fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 1,
+            num_children: 5,
+            sorted: PhantomData,
+        };
+        let survivors = |mut reinserter: GenerationalReinserter| -> Vec<i64> {
+            reinserter.reinsert(population()).unwrap().individuals.iter().map(|individual| individual.genome[0]).collect()
+        };
+
+        assert_eq!(survivors(GenerationalReinserter::default()), vec![8, 6, 4, 2, 0]);
+        assert_eq!(survivors(GenerationalReinserter::default().with_elites(2)), vec![9, 7, 8, 6, 4]);
+    }
+}
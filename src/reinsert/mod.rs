@@ -1,3 +1,43 @@
+use std::marker::PhantomData;
+
+use crate::types::*;
+use crate::individual::*;
+use crate::population::*;
+
 pub mod elitist;
+pub mod nsga2;
+pub mod steady_state;
+pub mod mu_lambda;
+pub mod generational;
+pub mod survivor;
+pub mod crowding;
+
+pub use elitist::*;
+pub use nsga2::*;
+pub use steady_state::*;
+pub use mu_lambda::*;
+pub use generational::*;
+pub use survivor::*;
+pub use crowding::*;
+
+type Individuals<G, F> = Vec<Individual<G, F>>;
+
+/// Take the individuals out of `population`, split into the previous generation and the
+/// children born in the current generation, both best first.
+pub(crate) fn split_generations<G: Genotype, F: Fitness>(population: &mut SortedPopulation<G, F>) -> (Individuals<G, F>, Individuals<G, F>) {
+    let generation = population.generation;
+    let (children, parents) = std::mem::take(&mut population.individuals)
+        .into_iter()
+        .partition(|individual| individual.generation == generation);
+    (parents, children)
+}
 
-pub use elitist::*;
\ No newline at end of file
+/// The next generation of `population`, made of `survivors`.
+pub(crate) fn with_survivors<G: Genotype, F: Fitness, S>(population: &Population<G, F, S>, survivors: Vec<Individual<G, F>>) -> UnsortedPopulation<G, F> {
+    UnsortedPopulation {
+        individuals: survivors,
+        generation: population.generation,
+        num_children: population.num_children,
+        sorted: PhantomData,
+    }
+}
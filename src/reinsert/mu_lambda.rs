@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::population::*;
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::{split_generations, with_survivors};
+
+/// (μ,λ) reinsertion: the parents are discarded and the best μ children survive.
+///
+/// μ defaults to the size of the previous generation. There must be at least μ children.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MuCommaLambda {
+    pub mu: Option<usize>,
+}
+
+impl MuCommaLambda {
+    pub fn with_mu(mut self, mu: usize) -> Self {
+        self.mu = Some(mu);
+        self
+    }
+}
+
+impl<G: Genotype, F: Fitness> ReinsertOperator<G, F> for MuCommaLambda {
+    fn reinsert(&mut self, mut population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        let mu = self.mu.unwrap_or_else(|| population.previous_generation_size());
+        let (_, children) = split_generations(&mut population);
+        if children.len() < mu {
+            return Err(Error::Genetic(format!("(μ,λ) reinsertion needs at least {} children, got {}", mu, children.len())));
+        }
+        Ok(with_survivors(&population, children.into_iter().take(mu).collect()))
+    }
+}
+
+/// (μ+λ) reinsertion: the best μ of parents and children survive.
+///
+/// μ defaults to the size of the previous generation, which makes it the same as
+/// [`ElitistReinserter`](super::ElitistReinserter).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MuPlusLambda {
+    pub mu: Option<usize>,
+}
+
+impl MuPlusLambda {
+    pub fn with_mu(mut self, mu: usize) -> Self {
+        self.mu = Some(mu);
+        self
+    }
+}
+
+impl<G: Genotype, F: Fitness> ReinsertOperator<G, F> for MuPlusLambda {
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        let mu = self.mu.unwrap_or_else(|| population.previous_generation_size());
+        Ok(population.truncate(mu))
+    }
+}
+
+impl Stateful for MuCommaLambda {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+impl Stateful for MuPlusLambda {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use crate::individual::*;
+    use super::*;
+
+    // Parents have odd fitnesses and children even ones.
+    fn test_population() -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: (0..10)
+                .rev()
+                .map(|fitness| Individual { generation: (1 - fitness % 2) as u64, genome: vec![fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 1,
+            num_children: 5,
+            sorted: PhantomData,
+        }
+    }
+
+    fn survivors(population: UnsortedPopulation<Vec<i64>, i64>) -> Vec<i64> {
+        population.individuals.iter().map(|individual| individual.genome[0]).collect()
+    }
+
+    #[test]
+    fn test_mu_comma_lambda_discards_parents() {
+        assert_eq!(survivors(MuCommaLambda::default().reinsert(test_population()).unwrap()), vec![8, 6, 4, 2, 0]);
+        assert_eq!(survivors(MuCommaLambda::default().with_mu(2).reinsert(test_population()).unwrap()), vec![8, 6]);
+        assert!(MuCommaLambda::default().with_mu(6).reinsert(test_population()).is_err());
+    }
+
+    #[test]
+    fn test_mu_plus_lambda() {
+        assert_eq!(survivors(MuPlusLambda::default().reinsert(test_population()).unwrap()), vec![9, 8, 7, 6, 5]);
+        assert_eq!(survivors(MuPlusLambda::default().with_mu(3).reinsert(test_population()).unwrap()), vec![9, 8, 7]);
+    }
+}
@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use crate::population::*;
+use crate::individual::*;
+use crate::types::*;
+use crate::error::*;
+use crate::fitness::{ParetoFitness, crowding_distances, fronts};
+use crate::checkpoint::Stateful;
+
+/// Environmental selection of NSGA-II.
+///
+/// Parents and children are admitted front by front while whole fronts fit into the
+/// population. The last front that only partially fits is truncated by crowding
+/// distance, recomputed among the members of that front only.
+pub struct Nsga2Reinserter;
+
+impl<G: Genotype> ReinsertOperator<G, ParetoFitness> for Nsga2Reinserter {
+    fn reinsert(&mut self, population: SortedPopulation<G, ParetoFitness>) -> Result<UnsortedPopulation<G, ParetoFitness>> {
+        let pop_size = population.previous_generation_size();
+        let ranks = population.individuals
+            .iter()
+            .map(|individual| individual.fitness.as_ref().map(ParetoFitness::rank))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::Genetic("NSGA-II reinsertion requires evaluated individuals".into()))?;
+
+        let mut individuals: Vec<Option<Individual<G, ParetoFitness>>> = population.individuals
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut survivors = Vec::with_capacity(pop_size);
+
+        for front in fronts(&ranks) {
+            let remaining = pop_size - survivors.len();
+            if remaining == 0 {
+                break;
+            }
+
+            if front.len() <= remaining {
+                survivors.extend(front.iter().filter_map(|&i| individuals[i].take()));
+                continue;
+            }
+
+            let objectives: Vec<Vec<f64>> = front
+                .iter()
+                .map(|&i| individuals[i].as_ref().unwrap().fitness.as_ref().unwrap().objectives().to_vec())
+                .collect();
+            let distances = crowding_distances(&objectives);
+            let mut by_crowding: Vec<usize> = (0..front.len()).collect();
+            by_crowding.sort_by(|&a, &b| distances[b].total_cmp(&distances[a]));
+
+            survivors.extend(
+                by_crowding
+                    .into_iter()
+                    .take(remaining)
+                    .filter_map(|j| individuals[front[j]].take())
+            );
+        }
+
+        Ok(UnsortedPopulation {
+            individuals: survivors,
+            generation: population.generation,
+            num_children: population.num_children,
+            sorted: PhantomData,
+        })
+    }
+}
+
+impl Stateful for Nsga2Reinserter {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use super::*;
+
+    fn sorted_population(objectives: Vec<Vec<f64>>, num_children: usize) -> SortedPopulation<Vec<f64>, ParetoFitness> {
+        let fitness_function = ParetoFitnessFunction::default().with_objectives(|p: &Vec<f64>| p.clone());
+        let mut population = UnsortedPopulation::default()
+            .add_children(objectives)
+            .sort(&IdentityIncubator::default(), &fitness_function)
+            .unwrap();
+        population.num_children = num_children;
+        population
+    }
+
+    #[test]
+    fn test_last_front_is_truncated_by_crowding() {
+        // First front: (3, 3). Second front: five points of which only the two
+        // extremes, with infinite crowding distance, fit into the population.
+        let population = sorted_population(vec![
+            vec![3.0, 3.0],
+            vec![0.0, 2.0],
+            vec![0.1, 1.9],
+            vec![1.0, 1.0],
+            vec![1.9, 0.1],
+            vec![2.0, 0.0],
+        ], 3);
+
+        let survivors = Nsga2Reinserter.reinsert(population).unwrap();
+        let mut genomes: Vec<_> = survivors.individuals.iter().map(|individual| individual.genome.clone()).collect();
+        genomes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(genomes, vec![vec![0.0, 2.0], vec![2.0, 0.0], vec![3.0, 3.0]]);
+    }
+
+    #[test]
+    fn test_nsga2_approaches_front() {
+        struct Shift(ChaCha8Rng);
+
+        impl MutateOperator for Shift {
+            type Genotype = Vec<f64>;
+
+            fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+                genome[0] = (genome[0] + self.0.gen_range(-0.1..0.1)).clamp(0.0, 1.0);
+                Ok(())
+            }
+        }
+
+        // Maximize (x, 1 - x^2): every x in [0, 1] is Pareto optimal, and the
+        // population should spread along the whole front.
+        let fitness_function = ParetoFitnessFunction::default()
+            .with_objectives(|p: &Vec<f64>| vec![p[0], 1.0 - p[0] * p[0]]);
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(fitness_function)
+            .with_select(CrowdedTournamentSelectionBuilder::default()
+                .with_num_children(10)
+                .with_rng(ChaCha8Rng::seed_from_u64(0))
+                .build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(Shift(ChaCha8Rng::seed_from_u64(2)))
+            .with_reinsert(Nsga2Reinserter);
+
+        let population = builder.create_population(vec![vec![0.5]; 20]).unwrap();
+        let summary = builder.build().run(population, MaxGenerations(50)).unwrap();
+
+        let xs: Vec<f64> = summary.population.individuals.iter().map(|individual| individual.genome[0]).collect();
+        assert_eq!(xs.len(), 20);
+        assert!(xs.iter().any(|x| *x < 0.1));
+        assert!(xs.iter().any(|x| *x > 0.9));
+    }
+}
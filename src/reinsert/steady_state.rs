@@ -0,0 +1,190 @@
+//! Replacement operators for steady-state evolution with
+//! [`GeneticAlgorithm::run_steady_state`](crate::ga::GeneticAlgorithm::run_steady_state).
+//!
+//! Each offspring replaces at most one member of the population, chosen by the operator.
+
+use rand::Rng;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::population::*;
+use crate::individual::*;
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+
+/// Replace the worst individual.
+pub struct ReplaceWorst;
+
+impl<G: Genotype, F: Fitness> ReplaceOperator<G, F> for ReplaceWorst {
+    fn replace(&mut self, population: &SortedPopulation<G, F>, _child: &Individual<G, F>, _parents: &[usize]) -> Result<Option<usize>> {
+        Ok(population.individuals.len().checked_sub(1))
+    }
+}
+
+/// Replace an individual chosen uniformly at random, which may be the best one.
+pub struct ReplaceRandom<R: Rng> {
+    rng: R,
+}
+
+impl<R: Rng> ReplaceRandom<R> {
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl<R: Rng, G: Genotype, F: Fitness> ReplaceOperator<G, F> for ReplaceRandom<R> {
+    fn replace(&mut self, population: &SortedPopulation<G, F>, _child: &Individual<G, F>, _parents: &[usize]) -> Result<Option<usize>> {
+        let len = population.individuals.len();
+        Ok((len > 0).then(|| self.rng.gen_range(0..len)))
+    }
+}
+
+/// Replace the individual born in the earliest generation, the worst of them on ties.
+pub struct ReplaceOldest;
+
+impl<G: Genotype, F: Fitness> ReplaceOperator<G, F> for ReplaceOldest {
+    fn replace(&mut self, population: &SortedPopulation<G, F>, _child: &Individual<G, F>, _parents: &[usize]) -> Result<Option<usize>> {
+        Ok((0..population.individuals.len())
+            .rev()
+            .min_by_key(|&i| population.individuals[i].generation))
+    }
+}
+
+/// Replace the worst parent of the offspring if the offspring is better, otherwise discard
+/// the offspring.
+pub struct ReplaceParentIfBetter;
+
+impl<G: Genotype, F: Fitness> ReplaceOperator<G, F> for ReplaceParentIfBetter {
+    fn replace(&mut self, population: &SortedPopulation<G, F>, child: &Individual<G, F>, parents: &[usize]) -> Result<Option<usize>> {
+        // The population is sorted, so the worst parent has the largest index.
+        Ok(parents
+            .iter()
+            .copied()
+            .max()
+            .filter(|&parent| child.fitness > population.individuals[parent].fitness))
+    }
+}
+
+impl Stateful for ReplaceWorst {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+impl Stateful for ReplaceOldest {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+impl Stateful for ReplaceParentIfBetter {
+    type State = ();
+
+    fn state(&self) -> Self::State {}
+    fn restore(&mut self, _state: Self::State) {}
+}
+
+impl<R> Stateful for ReplaceRandom<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::marker::PhantomData;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::prelude::*;
+    use super::*;
+
+    fn test_population(fitnesses: Vec<(i64, u64)>) -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: fitnesses
+                .into_iter()
+                .map(|(fitness, generation)| Individual { generation, genome: vec![fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 5,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_replacement_policies() {
+        let population = test_population(vec![(9, 3), (7, 1), (4, 1), (2, 4)]);
+        let child = Individual { generation: 5, genome: vec![5], fitness: Some(5), id: None };
+
+        assert_eq!(ReplaceWorst.replace(&population, &child, &[]).unwrap(), Some(3));
+        assert_eq!(ReplaceOldest.replace(&population, &child, &[]).unwrap(), Some(2));
+        assert_eq!(ReplaceParentIfBetter.replace(&population, &child, &[0, 2]).unwrap(), Some(2));
+        assert_eq!(ReplaceParentIfBetter.replace(&population, &child, &[0, 1]).unwrap(), None);
+        assert_eq!(ReplaceParentIfBetter.replace(&population, &child, &[]).unwrap(), None);
+
+        let mut random = ReplaceRandom::new(ChaCha8Rng::seed_from_u64(0));
+        assert!(random.replace(&population, &child, &[]).unwrap().is_some_and(|i| i < 4));
+    }
+
+    #[test]
+    fn test_steady_state_evaluates_only_offspring() {
+        struct CountingFitness(Cell<usize>);
+
+        impl FitnessFunction for CountingFitness {
+            type Phenotype = Vec<i64>;
+            type Fitness = i64;
+
+            fn evaluate(&self, phenotypes_with_fitnesses: &[(&Self::Phenotype, Option<&Self::Fitness>)]) -> Result<Vec<Self::Fitness>> {
+                self.0.set(self.0.get() + phenotypes_with_fitnesses.len());
+                Ok(phenotypes_with_fitnesses.iter().map(|(phenotype, _)| phenotype.iter().sum()).collect())
+            }
+        }
+
+        struct Increment;
+
+        impl MutateOperator for Increment {
+            type Genotype = Vec<i64>;
+
+            fn mutate(&mut self, genome: &mut Self::Genotype) -> Result<()> {
+                genome[0] += 1;
+                Ok(())
+            }
+        }
+
+        let builder = GeneticAlgorithmBuilder::default()
+            .with_incubator(IdentityIncubator::default())
+            .with_fitness_function(CountingFitness(Cell::new(0)))
+            .with_select(TournamentSelectionBuilder::default()
+                .with_num_children(1)
+                .with_rng(ChaCha8Rng::seed_from_u64(0))
+                .build())
+            .with_crossover(UniformCrossoverBuilder::default().with_rng(ChaCha8Rng::seed_from_u64(1)).build().unwrap())
+            .with_mutate(Increment)
+            .with_reinsert(ReplaceWorst);
+
+        let population = builder.create_population((0..10).map(|i| vec![i, 0]).collect()).unwrap();
+        let mut ga = builder.build();
+        let summary = ga.run_steady_state(population, MaxGenerations(20)).unwrap();
+
+        // 10 initial individuals, then 2 children per step.
+        assert_eq!(summary.progress.evaluations, 50);
+        assert_eq!(ga.fitness_function.0.get(), 50);
+
+        let individuals = &summary.population.individuals;
+        assert_eq!(individuals.len(), 10);
+        assert!(individuals.windows(2).all(|pair| pair[0].fitness >= pair[1].fitness));
+        assert!(individuals.iter().all(|individual| individual.fitness == Some(individual.genome.iter().sum())));
+        assert!(summary.population.best().fitness > Some(9));
+    }
+}
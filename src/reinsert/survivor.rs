@@ -0,0 +1,240 @@
+//! Stochastic survivor selection, for less selection pressure than truncation.
+//!
+//! Both operators choose `mu` survivors among parents and children without replacement.
+//! `mu` defaults to the size of the previous generation.
+
+use rand::Rng;
+use rand::seq::index::sample;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::population::*;
+use crate::individual::*;
+use crate::types::*;
+use crate::error::*;
+use crate::checkpoint::Stateful;
+use super::with_survivors;
+
+/// Take the individuals at `indices` out of `population` as the survivors.
+fn take_survivors<G: Genotype, F: Fitness>(mut population: SortedPopulation<G, F>, mut indices: Vec<usize>) -> UnsortedPopulation<G, F> {
+    indices.sort_unstable();
+    let mut individuals: Vec<Option<Individual<G, F>>> = std::mem::take(&mut population.individuals)
+        .into_iter()
+        .map(Some)
+        .collect();
+    let survivors = indices
+        .into_iter()
+        .filter_map(|index| individuals[index].take())
+        .collect();
+    with_survivors(&population, survivors)
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct RandomReinserterBuilder<R> {
+    pub mu: Option<usize>,
+    rng: R
+}
+
+impl Default for RandomReinserterBuilder<()> {
+    fn default() -> Self {
+        Self {
+            mu: None,
+            rng: ()
+        }
+    }
+}
+
+impl<R> RandomReinserterBuilder<R> {
+    pub fn with_mu(mut self, mu: usize) -> Self {
+        self.mu = Some(mu);
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> RandomReinserterBuilder<RNG> {
+        RandomReinserterBuilder {
+            mu: self.mu,
+            rng
+        }
+    }
+}
+
+impl<R> RandomReinserterBuilder<R> where R: Rng {
+    pub fn build(self) -> RandomReinserter<R> {
+        RandomReinserter {
+            mu: self.mu,
+            rng: self.rng
+        }
+    }
+}
+
+/// Survivors are chosen uniformly at random among parents and children.
+pub struct RandomReinserter<R: Rng> {
+    pub mu: Option<usize>,
+    rng: R,
+}
+
+impl<R: Rng, G: Genotype, F: Fitness> ReinsertOperator<G, F> for RandomReinserter<R> {
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        let len = population.individuals.len();
+        let mu = self.mu.unwrap_or_else(|| population.previous_generation_size()).min(len);
+        let indices = sample(&mut self.rng, len, mu).into_vec();
+        Ok(take_survivors(population, indices))
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct TournamentReinserterBuilder<R> {
+    pub mu: Option<usize>,
+    pub tournament_size: usize,
+    rng: R
+}
+
+impl Default for TournamentReinserterBuilder<()> {
+    fn default() -> Self {
+        Self {
+            mu: None,
+            tournament_size: 2,
+            rng: ()
+        }
+    }
+}
+
+impl<R> TournamentReinserterBuilder<R> {
+    pub fn with_mu(mut self, mu: usize) -> Self {
+        self.mu = Some(mu);
+        self
+    }
+
+    pub fn with_tournament_size(mut self, size: usize) -> Self {
+        self.tournament_size = size;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> TournamentReinserterBuilder<RNG> {
+        TournamentReinserterBuilder {
+            mu: self.mu,
+            tournament_size: self.tournament_size,
+            rng
+        }
+    }
+}
+
+impl<R> TournamentReinserterBuilder<R> where R: Rng {
+    pub fn build(self) -> TournamentReinserter<R> {
+        TournamentReinserter {
+            mu: self.mu,
+            tournament_size: self.tournament_size,
+            rng: self.rng
+        }
+    }
+}
+
+/// Every survivor is the best of `tournament_size` distinct individuals drawn from those
+/// not chosen yet. Larger tournaments mean more selection pressure.
+pub struct TournamentReinserter<R: Rng> {
+    pub mu: Option<usize>,
+    pub tournament_size: usize,
+    rng: R,
+}
+
+impl<R: Rng, G: Genotype, F: Fitness> ReinsertOperator<G, F> for TournamentReinserter<R> {
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>> {
+        let len = population.individuals.len();
+        let mu = self.mu.unwrap_or_else(|| population.previous_generation_size()).min(len);
+        let tournament_size = self.tournament_size.max(1);
+
+        // The population is sorted, so the winner of a tournament is its smallest index.
+        let mut remaining: Vec<usize> = (0..len).collect();
+        let mut indices = Vec::with_capacity(mu);
+        for _ in 0..mu {
+            let winner = sample(&mut self.rng, remaining.len(), tournament_size.min(remaining.len()))
+                .into_iter()
+                .min_by_key(|&position| remaining[position])
+                .unwrap();
+            indices.push(remaining.swap_remove(winner));
+        }
+        Ok(take_survivors(population, indices))
+    }
+}
+
+impl<R> Stateful for RandomReinserter<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R> Stateful for TournamentReinserter<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::marker::PhantomData;
+    use super::*;
+
+    fn test_population(size: i64) -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: (0..size)
+                .rev()
+                .map(|fitness| Individual { generation: (fitness % 2) as u64, genome: vec![fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 1,
+            num_children: (size / 2) as usize,
+            sorted: PhantomData,
+        }
+    }
+
+    fn survivors(population: UnsortedPopulation<Vec<i64>, i64>) -> Vec<i64> {
+        population.individuals.iter().map(|individual| individual.genome[0]).collect()
+    }
+
+    #[test]
+    fn test_random_reinserter() {
+        let mut reinserter = RandomReinserterBuilder::default().with_rng(StdRng::seed_from_u64(0)).build();
+        let survivors = survivors(reinserter.reinsert(test_population(20)).unwrap());
+        assert_eq!(survivors.len(), 10);
+        assert!(survivors.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn test_tournament_pressure() {
+        let total = |tournament_size| -> i64 {
+            (0..20)
+                .map(|seed| {
+                    let mut reinserter = TournamentReinserterBuilder::default()
+                        .with_mu(5)
+                        .with_tournament_size(tournament_size)
+                        .with_rng(StdRng::seed_from_u64(seed))
+                        .build();
+                    survivors(reinserter.reinsert(test_population(20)).unwrap()).iter().sum::<i64>()
+                })
+                .sum()
+        };
+
+        // Tournaments over the whole population are truncation.
+        assert_eq!(total(20), 20 * (19 + 18 + 17 + 16 + 15));
+        assert!(total(1) < total(3));
+    }
+}
@@ -1,3 +1,94 @@
+use crate::types::*;
+use crate::error::*;
+use crate::population::*;
+
 pub mod tournament;
+pub mod proportionate;
+pub mod rank;
+pub mod truncation;
+pub mod random;
+pub mod crowded_tournament;
+
+pub use tournament::*;
+pub use proportionate::*;
+pub use rank::*;
+pub use truncation::*;
+pub use random::*;
+pub use crowded_tournament::*;
+
+pub(crate) fn ensure_not_empty<G: Genotype, F: Fitness>(population: &SortedPopulation<G, F>) -> Result<()> {
+    if population.individuals.is_empty() {
+        return Err(Error::Genetic("Cannot select parents from an empty population".into()));
+    }
+    Ok(())
+}
+
+pub(crate) fn ensure_num_parents(num_parents: usize) -> Result<()> {
+    if num_parents == 0 {
+        return Err(Error::Genetic("Cannot select groups of 0 parents".into()));
+    }
+    Ok(())
+}
+
+/// Split the genomes of the individuals at `indices` into groups of `num_parents`, each best first.
+/// `num_parents` must not be 0.
+pub(crate) fn group_parents<'a, G: Genotype, F: Fitness>(population: &'a SortedPopulation<G, F>, indices: &[usize], num_parents: usize) -> Vec<Vec<&'a G>> {
+    indices
+        .chunks(num_parents)
+        .map(|group| {
+            // The population is sorted best first, and so are its indices.
+            let mut group = group.to_vec();
+            group.sort_unstable();
+            group.iter().map(|index| &population.individuals[*index].genome).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::marker::PhantomData;
+    use crate::individual::*;
+    use super::*;
+
+    fn test_population() -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: (0..20)
+                .rev()
+                .map(|fitness| Individual { generation: 0, genome: vec![fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 0,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    fn assert_best_first<S: SelectOperator<i64>>(mut select: S) {
+        for group in select.select(&test_population()).unwrap() {
+            assert!(group.windows(2).all(|pair| pair[0] >= pair[1]), "{:?}", group);
+        }
+    }
+
+    #[test]
+    fn test_groups_are_best_first() {
+        let rng = || StdRng::seed_from_u64(0);
+        assert_best_first(TournamentSelectionBuilder::default().with_num_parents(3).with_rng(rng()).build());
+        assert_best_first(RandomSelectionBuilder::default().with_num_parents(3).with_rng(rng()).build());
+        assert_best_first(StochasticUniversalSamplingBuilder::default().with_num_parents(3).with_rng(rng()).build());
+        assert_best_first(LinearRankSelectionBuilder::default().with_num_parents(3).with_rng(rng()).build());
+        assert_best_first(TruncationSelectionBuilder::default().with_num_parents(3).with_rng(rng()).build());
+    }
 
-pub use tournament::*;
\ No newline at end of file
+    #[test]
+    fn test_groups_of_no_parents_are_an_error() {
+        let population = test_population();
+        let rng = || StdRng::seed_from_u64(0);
+        assert!(TournamentSelectionBuilder::default().with_num_parents(0).with_rng(rng()).build().select(&population).is_err());
+        assert!(RandomSelectionBuilder::default().with_num_parents(0).with_rng(rng()).build().select(&population).is_err());
+        assert!(RouletteWheelSelectionBuilder::default().with_num_parents(0).with_rng(rng()).build().select(&population).is_err());
+        assert!(StochasticUniversalSamplingBuilder::default().with_num_parents(0).with_rng(rng()).build().select(&population).is_err());
+        assert!(ExponentialRankSelectionBuilder::default().with_num_parents(0).with_rng(rng()).build().select(&population).is_err());
+        assert!(TruncationSelectionBuilder::default().with_num_parents(0).with_rng(rng()).build().select(&population).is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! Fitness-proportionate selection.
+//!
+//! Both operators select parents with probability proportional to a non-negative weight,
+//! obtained from the [`ScalarFitness`] projection of the fitness and a [`WeightScaling`]. Both
+//! scalings take the direction of the fitness into account, so minimized fitnesses work too.
+
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::population::*;
+use crate::checkpoint::Stateful;
+use super::{group_parents, ensure_not_empty, ensure_num_parents};
+
+/// How scalar fitnesses are turned into selection weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum WeightScaling {
+    /// Use the [weight](ScalarFitness::to_weight) of the fitness. Negative fitnesses are an error.
+    #[default]
+    Raw,
+    /// Use the distance to the worst fitness of the population, so the worst individual gets
+    /// weight 0.
+    Windowing,
+}
+
+impl WeightScaling {
+    /// The selection weight of every individual of `population`, in population order.
+    ///
+    /// If all weights are zero, every individual gets the same weight.
+    pub fn weights<G, F>(&self, population: &SortedPopulation<G, F>) -> Result<Vec<f64>>
+        where
+            G: Genotype,
+            F: ScalarFitness,
+    {
+        let mut weights: Vec<f64> = match self {
+            WeightScaling::Raw => {
+                let weights = population
+                    .fitnesses()
+                    .map(|fitness| fitness.map_or(Ok(0.0), ScalarFitness::to_weight))
+                    .collect::<Result<Vec<f64>>>()?;
+                if weights.iter().any(|weight| !weight.is_finite()) {
+                    return Err(Error::Genetic("Fitness-proportionate selection requires finite fitnesses".into()));
+                }
+                weights
+            },
+            WeightScaling::Windowing => {
+                // Orient the values so that larger is better.
+                let sign = if F::MINIMIZED { -1.0 } else { 1.0 };
+                let mut weights: Vec<f64> = population
+                    .fitnesses()
+                    .map(|fitness| fitness.map_or(0.0, |fitness| sign * fitness.to_f64()))
+                    .collect();
+                let worst = weights.iter().copied().fold(f64::INFINITY, f64::min);
+                weights.iter_mut().for_each(|weight| *weight -= worst);
+                if weights.iter().any(|weight| !weight.is_finite()) {
+                    return Err(Error::Genetic("Fitness-proportionate selection requires finite fitnesses".into()));
+                }
+                weights
+            },
+        };
+
+        if weights.iter().all(|weight| *weight == 0.0) {
+            weights.iter_mut().for_each(|weight| *weight = 1.0);
+        }
+        Ok(weights)
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct RouletteWheelSelectionBuilder<R> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub scaling: WeightScaling,
+    rng: R
+}
+
+impl Default for RouletteWheelSelectionBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_children: 4,
+            num_parents: 2,
+            scaling: WeightScaling::default(),
+            rng: ()
+        }
+    }
+}
+
+impl<R> RouletteWheelSelectionBuilder<R> {
+    pub fn with_num_children(mut self, num_children: usize) -> Self {
+        self.num_children = num_children;
+        self
+    }
+
+    pub fn with_num_parents(mut self, num_parents: usize) -> Self {
+        self.num_parents = num_parents;
+        self
+    }
+
+    pub fn with_scaling(mut self, scaling: WeightScaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> RouletteWheelSelectionBuilder<RNG> {
+        RouletteWheelSelectionBuilder {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            scaling: self.scaling,
+            rng
+        }
+    }
+}
+
+impl<R> RouletteWheelSelectionBuilder<R> where R: Rng {
+    pub fn build(self) -> RouletteWheelSelection<R> {
+        RouletteWheelSelection {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            scaling: self.scaling,
+            rng: self.rng
+        }
+    }
+}
+
+/// Roulette-wheel selection: every parent is drawn independently, proportionally to its weight.
+pub struct RouletteWheelSelection<R: Rng> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub scaling: WeightScaling,
+    rng: R,
+}
+
+impl<R: Rng, F: ScalarFitness> SelectOperator<F> for RouletteWheelSelection<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+    where
+        G: Genotype,
+    {
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
+        let weights = self.scaling.weights(population)?;
+        let distribution = WeightedIndex::new(&weights).map_err(|err| Error::Genetic(err.to_string()))?;
+        let indices: Vec<usize> = (0..self.num_children * self.num_parents)
+            .map(|_| distribution.sample(&mut self.rng))
+            .collect();
+        Ok(group_parents(population, &indices, self.num_parents))
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct StochasticUniversalSamplingBuilder<R> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub scaling: WeightScaling,
+    rng: R
+}
+
+impl Default for StochasticUniversalSamplingBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_children: 4,
+            num_parents: 2,
+            scaling: WeightScaling::default(),
+            rng: ()
+        }
+    }
+}
+
+impl<R> StochasticUniversalSamplingBuilder<R> {
+    pub fn with_num_children(mut self, num_children: usize) -> Self {
+        self.num_children = num_children;
+        self
+    }
+
+    pub fn with_num_parents(mut self, num_parents: usize) -> Self {
+        self.num_parents = num_parents;
+        self
+    }
+
+    pub fn with_scaling(mut self, scaling: WeightScaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> StochasticUniversalSamplingBuilder<RNG> {
+        StochasticUniversalSamplingBuilder {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            scaling: self.scaling,
+            rng
+        }
+    }
+}
+
+impl<R> StochasticUniversalSamplingBuilder<R> where R: Rng {
+    pub fn build(self) -> StochasticUniversalSampling<R> {
+        StochasticUniversalSampling {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            scaling: self.scaling,
+            rng: self.rng
+        }
+    }
+}
+
+/// Stochastic universal sampling: all parents are selected with a single spin of a wheel with
+/// evenly spaced pointers, which keeps the number of selections of every individual close to its
+/// expected value.
+pub struct StochasticUniversalSampling<R: Rng> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub scaling: WeightScaling,
+    rng: R,
+}
+
+impl<R: Rng, F: ScalarFitness> SelectOperator<F> for StochasticUniversalSampling<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+    where
+        G: Genotype,
+    {
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
+        let weights = self.scaling.weights(population)?;
+        let num_selected = self.num_children * self.num_parents;
+        if num_selected == 0 {
+            return Ok(Vec::new());
+        }
+
+        let total: f64 = weights.iter().sum();
+        let spacing = total / num_selected as f64;
+        let mut pointer = self.rng.gen_range(0.0..spacing);
+        let mut indices = Vec::with_capacity(num_selected);
+        let mut accumulated = 0.0;
+        for (index, weight) in weights.iter().enumerate() {
+            accumulated += weight;
+            while pointer < accumulated && indices.len() < num_selected {
+                indices.push(index);
+                pointer += spacing;
+            }
+        }
+        // Rounding errors can leave the last pointers just past the end, which belongs to the
+        // last individual that can be selected. There is one, as not all weights are zero.
+        let last = weights.iter().rposition(|weight| *weight > 0.0).unwrap_or(weights.len() - 1);
+        indices.resize(num_selected, last);
+
+        // The pointers select parents in population order, shuffle them so that
+        // groups do not consist of neighbours.
+        indices.shuffle(&mut self.rng);
+        Ok(group_parents(population, &indices, self.num_parents))
+    }
+}
+
+impl<R> Stateful for RouletteWheelSelection<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R> Stateful for StochasticUniversalSampling<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::marker::PhantomData;
+    use crate::individual::*;
+    use crate::fitnesses::Minimize;
+    use super::*;
+
+    fn test_population(fitnesses: Vec<i64>) -> SortedPopulation<Vec<i64>, i64> {
+        SortedPopulation {
+            individuals: fitnesses
+                .into_iter()
+                .map(|fitness| Individual { generation: 0, genome: vec![fitness], fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 0,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_weight_scaling() {
+        let population = test_population(vec![5, 3, -1]);
+        assert!(WeightScaling::Raw.weights(&population).is_err());
+        assert_eq!(WeightScaling::Windowing.weights(&population).unwrap(), vec![6.0, 4.0, 0.0]);
+        assert_eq!(WeightScaling::Windowing.weights(&test_population(vec![2, 2])).unwrap(), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_weight_scaling_of_minimized_fitness() {
+        let population = SortedPopulation {
+            individuals: [0, 1, 3]
+                .into_iter()
+                .map(|cost| Individual { generation: 0, genome: vec![cost], fitness: Some(Minimize(cost)), id: None })
+                .collect(),
+            generation: 0,
+            num_children: 0,
+            sorted: PhantomData,
+        };
+        assert_eq!(WeightScaling::Raw.weights(&population).unwrap(), vec![1.0, 0.5, 0.25]);
+        assert_eq!(WeightScaling::Windowing.weights(&population).unwrap(), vec![3.0, 2.0, 0.0]);
+
+        let mut selection = RouletteWheelSelectionBuilder::default()
+            .with_num_children(100)
+            .with_scaling(WeightScaling::Windowing)
+            .with_rng(StdRng::seed_from_u64(0))
+            .build();
+        let parents = selection.select(&population).unwrap();
+        assert!(parents.iter().flatten().all(|parent| parent[0] != 3));
+    }
+
+    #[test]
+    fn test_stochastic_universal_sampling_is_proportional() {
+        let population = test_population(vec![6, 3, 1, 0]);
+        let mut selection = StochasticUniversalSamplingBuilder::default()
+            .with_num_children(5)
+            .with_rng(StdRng::seed_from_u64(0))
+            .build();
+
+        let parents = selection.select(&population).unwrap();
+        assert_eq!(parents.len(), 5);
+        assert!(parents.iter().all(|group| group.len() == 2));
+
+        let count = |genome: i64| parents.iter().flatten().filter(|parent| parent[0] == genome).count();
+        assert_eq!((count(6), count(3), count(1), count(0)), (6, 3, 1, 0));
+    }
+
+    #[test]
+    fn test_roulette_wheel_never_selects_zero_weights() {
+        let population = test_population(vec![4, 2, 0]);
+        let mut selection = RouletteWheelSelectionBuilder::default()
+            .with_num_children(100)
+            .with_rng(StdRng::seed_from_u64(0))
+            .build();
+
+        let parents = selection.select(&population).unwrap();
+        assert!(parents.iter().flatten().all(|parent| parent[0] != 0));
+        assert!(parents.iter().flatten().any(|parent| parent[0] == 2));
+    }
+}
@@ -0,0 +1,95 @@
+//! Uniform random selection, mostly useful as a baseline.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::population::*;
+use crate::checkpoint::Stateful;
+use super::{group_parents, ensure_not_empty, ensure_num_parents};
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct RandomSelectionBuilder<R> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    rng: R
+}
+
+impl Default for RandomSelectionBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_children: 4,
+            num_parents: 2,
+            rng: ()
+        }
+    }
+}
+
+impl<R> RandomSelectionBuilder<R> {
+    pub fn with_num_children(mut self, num_children: usize) -> Self {
+        self.num_children = num_children;
+        self
+    }
+
+    pub fn with_num_parents(mut self, num_parents: usize) -> Self {
+        self.num_parents = num_parents;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> RandomSelectionBuilder<RNG> {
+        RandomSelectionBuilder {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            rng
+        }
+    }
+}
+
+impl<R> RandomSelectionBuilder<R> where R: Rng {
+    pub fn build(self) -> RandomSelection<R> {
+        RandomSelection {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            rng: self.rng
+        }
+    }
+}
+
+/// Selects every parent uniformly at random, regardless of fitness.
+pub struct RandomSelection<R: Rng> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    rng: R,
+}
+
+impl<R: Rng, F: Fitness> SelectOperator<F> for RandomSelection<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+    where
+        G: Genotype,
+    {
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
+        let size = population.individuals.len();
+        let indices: Vec<usize> = (0..self.num_children * self.num_parents)
+            .map(|_| self.rng.gen_range(0..size))
+            .collect();
+        Ok(group_parents(population, &indices, self.num_parents))
+    }
+}
+
+impl<R> Stateful for RandomSelection<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
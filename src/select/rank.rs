@@ -0,0 +1,250 @@
+//! Rank-based selection.
+//!
+//! The selection probability of an individual only depends on its rank in the sorted
+//! population, so these work with any [`Fitness`] and are insensitive to its scale.
+
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::population::*;
+use crate::checkpoint::Stateful;
+use super::{group_parents, ensure_not_empty, ensure_num_parents};
+
+fn select_by_weights<'a, R, G, F>(rng: &mut R, population: &'a SortedPopulation<G, F>, weights: &[f64], num_children: usize, num_parents: usize) -> Result<Vec<Vec<&'a G>>>
+    where
+        R: Rng,
+        G: Genotype,
+        F: Fitness,
+{
+    let distribution = WeightedIndex::new(weights).map_err(|err| Error::Genetic(err.to_string()))?;
+    let indices: Vec<usize> = (0..num_children * num_parents)
+        .map(|_| distribution.sample(rng))
+        .collect();
+    Ok(group_parents(population, &indices, num_parents))
+}
+
+/// Linear ranking weights for a population of `size`, best first.
+///
+/// The best individual is `selection_pressure` times as likely to be selected as an average one,
+/// the worst `2 - selection_pressure` times.
+pub fn linear_rank_weights(size: usize, selection_pressure: f64) -> Vec<f64> {
+    if size < 2 {
+        return vec![1.0; size];
+    }
+    (0..size)
+        .map(|rank| {
+            let position = (size - 1 - rank) as f64 / (size - 1) as f64;
+            (2.0 - selection_pressure) + 2.0 * (selection_pressure - 1.0) * position
+        })
+        .collect()
+}
+
+/// Exponential ranking weights for a population of `size`, best first: `base^rank`.
+pub fn exponential_rank_weights(size: usize, base: f64) -> Vec<f64> {
+    (0..size).map(|rank| base.powi(rank as i32)).collect()
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct LinearRankSelectionBuilder<R> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub selection_pressure: f64,
+    rng: R
+}
+
+impl Default for LinearRankSelectionBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_children: 4,
+            num_parents: 2,
+            selection_pressure: 1.5,
+            rng: ()
+        }
+    }
+}
+
+impl<R> LinearRankSelectionBuilder<R> {
+    pub fn with_num_children(mut self, num_children: usize) -> Self {
+        self.num_children = num_children;
+        self
+    }
+
+    pub fn with_num_parents(mut self, num_parents: usize) -> Self {
+        self.num_parents = num_parents;
+        self
+    }
+
+    pub fn with_selection_pressure(mut self, selection_pressure: f64) -> Self {
+        self.selection_pressure = selection_pressure;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> LinearRankSelectionBuilder<RNG> {
+        LinearRankSelectionBuilder {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            selection_pressure: self.selection_pressure,
+            rng
+        }
+    }
+}
+
+impl<R> LinearRankSelectionBuilder<R> where R: Rng {
+    pub fn build(self) -> LinearRankSelection<R> {
+        LinearRankSelection {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            selection_pressure: self.selection_pressure,
+            rng: self.rng
+        }
+    }
+}
+
+/// Linear rank selection, see [`linear_rank_weights`]. `selection_pressure` must be in `[1, 2]`.
+pub struct LinearRankSelection<R: Rng> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub selection_pressure: f64,
+    rng: R,
+}
+
+impl<R: Rng, F: Fitness> SelectOperator<F> for LinearRankSelection<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+    where
+        G: Genotype,
+    {
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
+        if !(1.0..=2.0).contains(&self.selection_pressure) {
+            return Err(Error::Genetic("Selection pressure of linear rank selection must be in [1, 2]".into()));
+        }
+        let weights = linear_rank_weights(population.individuals.len(), self.selection_pressure);
+        select_by_weights(&mut self.rng, population, &weights, self.num_children, self.num_parents)
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ExponentialRankSelectionBuilder<R> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub base: f64,
+    rng: R
+}
+
+impl Default for ExponentialRankSelectionBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_children: 4,
+            num_parents: 2,
+            base: 0.95,
+            rng: ()
+        }
+    }
+}
+
+impl<R> ExponentialRankSelectionBuilder<R> {
+    pub fn with_num_children(mut self, num_children: usize) -> Self {
+        self.num_children = num_children;
+        self
+    }
+
+    pub fn with_num_parents(mut self, num_parents: usize) -> Self {
+        self.num_parents = num_parents;
+        self
+    }
+
+    pub fn with_base(mut self, base: f64) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> ExponentialRankSelectionBuilder<RNG> {
+        ExponentialRankSelectionBuilder {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            base: self.base,
+            rng
+        }
+    }
+}
+
+impl<R> ExponentialRankSelectionBuilder<R> where R: Rng {
+    pub fn build(self) -> ExponentialRankSelection<R> {
+        ExponentialRankSelection {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            base: self.base,
+            rng: self.rng
+        }
+    }
+}
+
+/// Exponential rank selection, see [`exponential_rank_weights`]. `base` must be in `(0, 1]`,
+/// smaller bases favour the best individuals more strongly.
+pub struct ExponentialRankSelection<R: Rng> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub base: f64,
+    rng: R,
+}
+
+impl<R: Rng, F: Fitness> SelectOperator<F> for ExponentialRankSelection<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+    where
+        G: Genotype,
+    {
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
+        if !(self.base > 0.0 && self.base <= 1.0) {
+            return Err(Error::Genetic("Base of exponential rank selection must be in (0, 1]".into()));
+        }
+        let weights = exponential_rank_weights(population.individuals.len(), self.base);
+        select_by_weights(&mut self.rng, population, &weights, self.num_children, self.num_parents)
+    }
+}
+
+impl<R> Stateful for LinearRankSelection<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+impl<R> Stateful for ExponentialRankSelection<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_weights() {
+        assert_eq!(linear_rank_weights(3, 2.0), vec![2.0, 1.0, 0.0]);
+        assert_eq!(linear_rank_weights(3, 1.0), vec![1.0, 1.0, 1.0]);
+        assert_eq!(exponential_rank_weights(3, 0.5), vec![1.0, 0.5, 0.25]);
+    }
+}
@@ -1,8 +1,12 @@
 use rand::*;
 use crate::types::*;
 use crate::error::Result;
+use crate::individual::*;
 use crate::population::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::checkpoint::Stateful;
+use super::{ensure_not_empty, ensure_num_parents};
 
 #[derive(Clone, Deserialize)]
 #[serde(default)]
@@ -79,17 +83,18 @@ pub struct TournamentSelection<R: Rng> {
     rng: R,
 }
 
-impl<R: Rng> SelectOperator for TournamentSelection<R> {
-    fn select<'a, G, F>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+impl<R: Rng, F: Fitness> SelectOperator<F> for TournamentSelection<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
     where
         G: Genotype,
-        F: Fitness,
     {
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
         let population_size = population.individuals.len();
         let mut selected_parents: Vec<Vec<&'a G>> = vec![];
 
         for _ in 0..self.num_children {
-            let mut parents: Vec<&'a G> = vec![];
+            let mut parents: Vec<&'a Individual<G, F>> = vec![];
             for _ in 0..self.num_parents {
                 let mut tournament = (0..self.tournament_size)
                     .map(|_| {
@@ -118,19 +123,33 @@ impl<R: Rng> SelectOperator for TournamentSelection<R> {
                         .expect("Tournament should have at least one participant")
                 });
 
-                parents.push(&selected_parent.genome);
+                parents.push(*selected_parent);
             }
-            selected_parents.push(parents);
+            parents.sort_by(|i1, i2| i2.fitness.cmp(&i1.fitness));
+            selected_parents.push(parents.into_iter().map(|individual| &individual.genome).collect());
         }
 
         Ok(selected_parents)
     }
 }
 
+impl<R> Stateful for TournamentSelection<R> 
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::population::*;
-    use crate::individual::*;
     use std::marker::PhantomData;
     use super::*;
     use rand::SeedableRng;
@@ -143,7 +162,8 @@ mod tests {
             .map(|x| Individual {
                 generation: 0,
                 genome: x,
-                fitness: Some(x)
+                fitness: Some(x),
+                id: None,
             })
             .collect();
 
@@ -0,0 +1,139 @@
+//! Truncation selection.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::types::*;
+use crate::error::*;
+use crate::population::*;
+use crate::checkpoint::Stateful;
+use super::{group_parents, ensure_not_empty, ensure_num_parents};
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct TruncationSelectionBuilder<R> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub proportion: f64,
+    rng: R
+}
+
+impl Default for TruncationSelectionBuilder<()> {
+    fn default() -> Self {
+        Self {
+            num_children: 4,
+            num_parents: 2,
+            proportion: 0.5,
+            rng: ()
+        }
+    }
+}
+
+impl<R> TruncationSelectionBuilder<R> {
+    pub fn with_num_children(mut self, num_children: usize) -> Self {
+        self.num_children = num_children;
+        self
+    }
+
+    pub fn with_num_parents(mut self, num_parents: usize) -> Self {
+        self.num_parents = num_parents;
+        self
+    }
+
+    pub fn with_proportion(mut self, proportion: f64) -> Self {
+        self.proportion = proportion;
+        self
+    }
+
+    pub fn with_rng<RNG: Rng>(self, rng: RNG) -> TruncationSelectionBuilder<RNG> {
+        TruncationSelectionBuilder {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            proportion: self.proportion,
+            rng
+        }
+    }
+}
+
+impl<R> TruncationSelectionBuilder<R> where R: Rng {
+    pub fn build(self) -> TruncationSelection<R> {
+        TruncationSelection {
+            num_children: self.num_children,
+            num_parents: self.num_parents,
+            proportion: self.proportion,
+            rng: self.rng
+        }
+    }
+}
+
+/// Selects parents uniformly at random among the best `proportion` of the population.
+pub struct TruncationSelection<R: Rng> {
+    pub num_children: usize,
+    pub num_parents: usize,
+    pub proportion: f64,
+    rng: R,
+}
+
+impl<R: Rng, F: Fitness> SelectOperator<F> for TruncationSelection<R> {
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+    where
+        G: Genotype,
+    {
+        if !(self.proportion > 0.0 && self.proportion <= 1.0) {
+            return Err(Error::Genetic("Proportion of truncation selection must be in (0, 1]".into()));
+        }
+        ensure_not_empty(population)?;
+        ensure_num_parents(self.num_parents)?;
+        let size = ((population.individuals.len() as f64 * self.proportion).ceil() as usize)
+            .clamp(1, population.individuals.len());
+        let indices: Vec<usize> = (0..self.num_children * self.num_parents)
+            .map(|_| self.rng.gen_range(0..size))
+            .collect();
+        Ok(group_parents(population, &indices, self.num_parents))
+    }
+}
+
+impl<R> Stateful for TruncationSelection<R>
+    where R: Rng + Clone + Serialize + DeserializeOwned
+{
+    type State = R;
+
+    fn state(&self) -> Self::State {
+        self.rng.clone()
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        self.rng = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::marker::PhantomData;
+    use crate::individual::*;
+    use super::*;
+
+    #[test]
+    fn test_truncation_selection_only_selects_the_best() {
+        let population: SortedPopulation<usize, usize> = SortedPopulation {
+            individuals: (0..100).rev().map(|x| Individual { generation: 0, genome: x, fitness: Some(x), id: None }).collect(),
+            generation: 0,
+            num_children: 0,
+            sorted: PhantomData,
+        };
+        let mut selection = TruncationSelectionBuilder::default()
+            .with_proportion(0.1)
+            .with_num_children(50)
+            .with_num_parents(3)
+            .with_rng(StdRng::seed_from_u64(0))
+            .build();
+
+        let parents = selection.select(&population).unwrap();
+        assert_eq!(parents.len(), 50);
+        assert!(parents.iter().all(|group| group.len() == 3));
+        assert!(parents.iter().flatten().all(|parent| **parent >= 90));
+    }
+}
@@ -0,0 +1,272 @@
+//! Per-generation statistics.
+//!
+//! [`StatisticsCollector`] is an [`Observer`] that records a [`GenerationStats`]
+//! for every call to `advance`, for any fitness implementing [`ScalarFitness`].
+
+use std::io::Write;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::*;
+use crate::population::*;
+use crate::observer::*;
+use crate::error::*;
+
+/// Serializes non-finite values as `null`, which JSON cannot represent, and reads `null` back
+/// as NaN.
+mod non_finite_as_null {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_some(value)
+        } else {
+            serializer.serialize_none()
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+    }
+}
+
+/// Fitness statistics of a generation. The fitness statistics of an empty population are NaN.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub generation: u64,
+    #[serde(with = "non_finite_as_null")]
+    pub best: f64,
+    #[serde(with = "non_finite_as_null")]
+    pub mean: f64,
+    #[serde(with = "non_finite_as_null")]
+    pub median: f64,
+    #[serde(with = "non_finite_as_null")]
+    pub worst: f64,
+    #[serde(with = "non_finite_as_null")]
+    pub std_dev: f64,
+    /// Number of offspring evaluated during this generation.
+    pub evaluations: u64,
+    /// Number of offspring evaluated since the collector was created.
+    pub total_evaluations: u64,
+    /// Wall-clock duration of the generation, in seconds.
+    pub duration: f64,
+}
+
+impl GenerationStats {
+    pub const CSV_HEADER: &'static str = "generation,best,mean,median,worst,std_dev,evaluations,total_evaluations,duration";
+
+    /// Compute statistics of a sorted population. Individuals without fitness are ignored.
+    pub fn from_population<G, F>(population: &SortedPopulation<G, F>, evaluations: u64, total_evaluations: u64, duration: Duration) -> Self
+        where
+            G: Genotype,
+            F: ScalarFitness,
+    {
+        // The population is sorted best first, so the values are in descending order, or in
+        // ascending order for minimized fitnesses.
+        let values: Vec<f64> = population
+            .fitnesses()
+            .flatten()
+            .map(|fitness| fitness.to_f64())
+            .collect();
+
+        let len = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / len;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / len;
+        let median = match values.len() {
+            0 => f64::NAN,
+            n if n % 2 == 0 => (values[n / 2 - 1] + values[n / 2]) / 2.0,
+            n => values[n / 2],
+        };
+
+        Self {
+            generation: population.generation,
+            best: values.first().copied().unwrap_or(f64::NAN),
+            mean,
+            median,
+            worst: values.last().copied().unwrap_or(f64::NAN),
+            std_dev: variance.sqrt(),
+            evaluations,
+            total_evaluations,
+            duration: duration.as_secs_f64(),
+        }
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.generation, self.best, self.mean, self.median, self.worst,
+            self.std_dev, self.evaluations, self.total_evaluations, self.duration
+        )
+    }
+}
+
+/// Records a [`GenerationStats`] per generation.
+pub struct StatisticsCollector<F> {
+    history: Vec<GenerationStats>,
+    started: Option<Instant>,
+    evaluations: u64,
+    total_evaluations: u64,
+    _phantom: PhantomData<F>,
+}
+
+impl<F> Default for StatisticsCollector<F> {
+    fn default() -> Self {
+        Self {
+            history: Vec::default(),
+            started: None,
+            evaluations: 0,
+            total_evaluations: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F> StatisticsCollector<F> {
+    pub fn history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    pub fn last(&self) -> Option<&GenerationStats> {
+        self.history.last()
+    }
+
+    /// Write the history as CSV, with a header line.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "{}", GenerationStats::CSV_HEADER)?;
+        for stats in self.history.iter() {
+            writeln!(writer, "{}", stats.to_csv_row())?;
+        }
+        Ok(())
+    }
+
+    /// Write the history as JSON lines, one object per generation.
+    ///
+    /// Non-finite values (e.g. the statistics of an empty population) are written as `null`,
+    /// and read back as NaN.
+    pub fn write_json_lines<W: Write>(&self, mut writer: W) -> Result<()> {
+        for stats in self.history.iter() {
+            serde_json::to_writer(&mut writer, stats)?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<G, F> Observer<G, F> for StatisticsCollector<F>
+    where
+        G: Genotype,
+        F: ScalarFitness,
+{
+    fn before_select(&mut self, _population: &SortedPopulation<G, F>) -> Result<()> {
+        self.started = Some(Instant::now());
+        self.evaluations = 0;
+        Ok(())
+    }
+
+    fn after_mutate(&mut self, _population: &SortedPopulation<G, F>, offspring: &[G]) -> Result<()> {
+        self.evaluations = offspring.len() as u64;
+        Ok(())
+    }
+
+    fn after_reinsert(&mut self, population: &SortedPopulation<G, F>) -> Result<()> {
+        let duration = self.started.take().map(|started| started.elapsed()).unwrap_or_default();
+        self.total_evaluations += self.evaluations;
+        self.history.push(GenerationStats::from_population(population, self.evaluations, self.total_evaluations, duration));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::individual::*;
+    use crate::fitnesses::Minimize;
+    use super::*;
+
+    fn test_population(fitnesses: Vec<i64>) -> SortedPopulation<usize, i64> {
+        SortedPopulation {
+            individuals: fitnesses
+                .into_iter()
+                .map(|fitness| Individual { generation: 3, genome: 0, fitness: Some(fitness), id: None })
+                .collect(),
+            generation: 3,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_generation_stats() {
+        let population = test_population(vec![7, 5, 4, 4]);
+        let stats = GenerationStats::from_population(&population, 2, 10, Duration::from_millis(500));
+
+        assert_eq!(stats.generation, 3);
+        assert_eq!(stats.best, 7.0);
+        assert_eq!(stats.worst, 4.0);
+        assert_eq!(stats.mean, 5.0);
+        assert_eq!(stats.median, 4.5);
+        assert_eq!(stats.std_dev, 1.5_f64.sqrt());
+        assert_eq!(stats.duration, 0.5);
+    }
+
+    #[test]
+    fn test_generation_stats_of_minimized_fitness() {
+        let population = SortedPopulation {
+            individuals: [1, 2, 6]
+                .into_iter()
+                .map(|cost| Individual { generation: 3, genome: 0usize, fitness: Some(Minimize(cost as i64)), id: None })
+                .collect(),
+            generation: 3,
+            num_children: 0,
+            sorted: PhantomData,
+        };
+        let stats = GenerationStats::from_population(&population, 0, 0, Duration::ZERO);
+
+        assert_eq!((stats.best, stats.mean, stats.worst), (1.0, 3.0, 6.0));
+    }
+
+    #[test]
+    fn test_export() {
+        let mut collector = StatisticsCollector::default();
+        let population = test_population(vec![3, 2, 1]);
+        collector.before_select(&population).unwrap();
+        collector.after_mutate(&population, &[1, 2]).unwrap();
+        collector.after_reinsert(&population).unwrap();
+
+        let mut csv = Vec::new();
+        collector.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], GenerationStats::CSV_HEADER);
+        assert!(lines[1].starts_with("3,3,2,2,1,"));
+
+        let mut json = Vec::new();
+        collector.write_json_lines(&mut json).unwrap();
+        let parsed: GenerationStats = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed.evaluations, 2);
+        assert_eq!(parsed.total_evaluations, 2);
+    }
+
+    #[test]
+    fn test_json_lines_round_trip() {
+        let mut collector = StatisticsCollector::default();
+        for population in [test_population(vec![3, 2, 1]), test_population(vec![])] {
+            collector.before_select(&population).unwrap();
+            collector.after_reinsert(&population).unwrap();
+        }
+
+        let mut json = Vec::new();
+        collector.write_json_lines(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.lines().nth(1).unwrap().contains("\"best\":null"));
+
+        let parsed: Vec<GenerationStats> = json
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed[0], collector.history()[0]);
+        assert!(parsed[1].best.is_nan() && parsed[1].mean.is_nan() && parsed[1].std_dev.is_nan());
+        assert_eq!(parsed[1].total_evaluations, collector.history()[1].total_evaluations);
+    }
+}
@@ -0,0 +1,255 @@
+//! Termination criteria for [`GeneticAlgorithm::run`](crate::ga::GeneticAlgorithm::run).
+//!
+//! A criterion is checked once before the first generation and then after
+//! every call to `advance`. Criteria can be combined with [`any`] and [`all`].
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::*;
+use crate::population::*;
+
+/// Progress of a run, as seen by termination criteria.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunProgress {
+    /// Number of generations advanced since the run started.
+    pub generations: u64,
+    /// Number of individuals evaluated for the first time, i.e. the children of every
+    /// generation plus the initial population.
+    ///
+    /// Individuals that are evaluated again are not counted: a fitness function that
+    /// recalculates existing fitnesses evaluates the whole population twice per
+    /// [`advance`](crate::ga::GeneticAlgorithm::advance), after adding the children and after
+    /// reinsertion, and does more work than this reports.
+    pub evaluations: u64,
+    /// Wall-clock time spent in the run.
+    pub elapsed: Duration,
+}
+
+/// The reason a run was stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    MaxGenerations(u64),
+    TimeLimit(Duration),
+    MaxEvaluations(u64),
+    TargetFitness,
+    Stagnation(u64),
+    /// Every criterion of an [`All`] combinator fired at once.
+    All(Vec<StopReason>),
+}
+
+pub trait TerminationCriterion<G, F>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    /// Returns `Some` with the reason if the run should stop.
+    fn check(&mut self, population: &SortedPopulation<G, F>, progress: &RunProgress) -> Option<StopReason>;
+}
+
+/// Stop when either `a` or `b` fires.
+pub fn any<A, B>(a: A, b: B) -> Any<A, B> {
+    Any(a, b)
+}
+
+/// Stop only when both `a` and `b` fire in the same generation.
+pub fn all<A, B>(a: A, b: B) -> All<A, B> {
+    All(a, b)
+}
+
+/// Stop after a fixed number of generations.
+#[derive(Debug, Clone)]
+pub struct MaxGenerations(pub u64);
+
+impl<G, F> TerminationCriterion<G, F> for MaxGenerations
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    fn check(&mut self, _population: &SortedPopulation<G, F>, progress: &RunProgress) -> Option<StopReason> {
+        (progress.generations >= self.0).then_some(StopReason::MaxGenerations(self.0))
+    }
+}
+
+/// Stop once the run has used up its wall-clock budget.
+///
+/// The budget is checked between generations, so a run may exceed it by up to one generation.
+#[derive(Debug, Clone)]
+pub struct TimeLimit(pub Duration);
+
+impl<G, F> TerminationCriterion<G, F> for TimeLimit
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    fn check(&mut self, _population: &SortedPopulation<G, F>, progress: &RunProgress) -> Option<StopReason> {
+        (progress.elapsed >= self.0).then_some(StopReason::TimeLimit(self.0))
+    }
+}
+
+/// Stop after a number of fitness evaluations, as counted by [`RunProgress::evaluations`].
+#[derive(Debug, Clone)]
+pub struct MaxEvaluations(pub u64);
+
+impl<G, F> TerminationCriterion<G, F> for MaxEvaluations
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    fn check(&mut self, _population: &SortedPopulation<G, F>, progress: &RunProgress) -> Option<StopReason> {
+        (progress.evaluations >= self.0).then_some(StopReason::MaxEvaluations(self.0))
+    }
+}
+
+/// Stop as soon as the best individual's fitness is at least the target.
+#[derive(Debug, Clone)]
+pub struct TargetFitness<F>(pub F);
+
+impl<G, F> TerminationCriterion<G, F> for TargetFitness<F>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    fn check(&mut self, population: &SortedPopulation<G, F>, _progress: &RunProgress) -> Option<StopReason> {
+        let reached = population.best().fitness.as_ref().is_some_and(|fitness| *fitness >= self.0);
+        reached.then_some(StopReason::TargetFitness)
+    }
+}
+
+/// Stop when the best fitness has not improved for a number of generations.
+#[derive(Debug, Clone)]
+pub struct Stagnation<F> {
+    pub generations: u64,
+    best: Option<F>,
+    stagnant_for: u64,
+}
+
+impl<F> Stagnation<F> {
+    pub fn new(generations: u64) -> Self {
+        Self { generations, best: None, stagnant_for: 0 }
+    }
+}
+
+impl<G, F> TerminationCriterion<G, F> for Stagnation<F>
+    where
+        G: Genotype,
+        F: Fitness,
+{
+    fn check(&mut self, population: &SortedPopulation<G, F>, _progress: &RunProgress) -> Option<StopReason> {
+        let current = population.best().fitness.as_ref();
+        let improved = match (&self.best, current) {
+            (Some(best), Some(current)) => current > best,
+            (None, Some(_)) => true,
+            _ => false,
+        };
+
+        if improved {
+            self.best = current.cloned();
+            self.stagnant_for = 0;
+        } else {
+            self.stagnant_for += 1;
+        }
+
+        (self.stagnant_for >= self.generations).then_some(StopReason::Stagnation(self.generations))
+    }
+}
+
+/// Fires when any of the two criteria fires. Both are always checked so that
+/// stateful criteria such as [`Stagnation`] see every generation.
+#[derive(Debug, Clone)]
+pub struct Any<A, B>(pub A, pub B);
+
+impl<G, F, A, B> TerminationCriterion<G, F> for Any<A, B>
+    where
+        G: Genotype,
+        F: Fitness,
+        A: TerminationCriterion<G, F>,
+        B: TerminationCriterion<G, F>,
+{
+    fn check(&mut self, population: &SortedPopulation<G, F>, progress: &RunProgress) -> Option<StopReason> {
+        let a = self.0.check(population, progress);
+        let b = self.1.check(population, progress);
+        a.or(b)
+    }
+}
+
+/// Fires when both criteria fire in the same generation.
+#[derive(Debug, Clone)]
+pub struct All<A, B>(pub A, pub B);
+
+impl<G, F, A, B> TerminationCriterion<G, F> for All<A, B>
+    where
+        G: Genotype,
+        F: Fitness,
+        A: TerminationCriterion<G, F>,
+        B: TerminationCriterion<G, F>,
+{
+    fn check(&mut self, population: &SortedPopulation<G, F>, progress: &RunProgress) -> Option<StopReason> {
+        let a = self.0.check(population, progress);
+        let b = self.1.check(population, progress);
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let mut reasons = Vec::new();
+                for reason in [a, b] {
+                    match reason {
+                        StopReason::All(nested) => reasons.extend(nested),
+                        reason => reasons.push(reason),
+                    }
+                }
+                Some(StopReason::All(reasons))
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use crate::individual::*;
+    use super::*;
+
+    fn population_with_best(best: usize) -> SortedPopulation<usize, usize> {
+        SortedPopulation {
+            individuals: vec![Individual { generation: 0, genome: best, fitness: Some(best), id: None }],
+            generation: 0,
+            num_children: 0,
+            sorted: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_stagnation() {
+        let mut criterion = Stagnation::new(2);
+        let progress = RunProgress::default();
+
+        assert_eq!(criterion.check(&population_with_best(1), &progress), None);
+        assert_eq!(criterion.check(&population_with_best(1), &progress), None);
+        assert_eq!(criterion.check(&population_with_best(2), &progress), None);
+        assert_eq!(criterion.check(&population_with_best(2), &progress), None);
+        assert_eq!(criterion.check(&population_with_best(2), &progress), Some(StopReason::Stagnation(2)));
+    }
+
+    #[test]
+    fn test_combinators() {
+        let population = population_with_best(5);
+        let progress = RunProgress { generations: 10, evaluations: 100, elapsed: Duration::ZERO };
+
+        let mut criterion = any(MaxGenerations(20), TargetFitness(5));
+        assert_eq!(criterion.check(&population, &progress), Some(StopReason::TargetFitness));
+
+        let mut criterion = all(MaxGenerations(20), TargetFitness(5));
+        assert_eq!(criterion.check(&population, &progress), None);
+
+        let mut criterion = all(all(MaxGenerations(10), MaxEvaluations(100)), TargetFitness(5));
+        assert_eq!(
+            criterion.check(&population, &progress),
+            Some(StopReason::All(vec![
+                StopReason::MaxGenerations(10),
+                StopReason::MaxEvaluations(100),
+                StopReason::TargetFitness
+            ]))
+        );
+    }
+}
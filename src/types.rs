@@ -1,12 +1,35 @@
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::population::*;
+use crate::individual::Individual;
 
 pub trait Genotype: Clone {}
 pub trait Phenotype: Clone {}
 pub trait Fitness: Clone + Ord {}
 
+/// A fitness that can be projected onto a single `f64`.
+pub trait ScalarFitness: Fitness {
+    /// Whether smaller values of [`to_f64`](Self::to_f64) are better.
+    const MINIMIZED: bool = false;
+
+    /// The fitness as it is reported, e.g. the cost of a minimized fitness.
+    fn to_f64(&self) -> f64;
+
+    /// A non-negative weight, larger for better fitnesses, for fitness-proportionate operators.
+    ///
+    /// The weight of a maximized fitness is its value, that of a minimized one `1 / (1 + cost)`.
+    /// Negative values have no weight and are an error.
+    fn to_weight(&self) -> Result<f64> {
+        let value = self.to_f64();
+        if value.is_nan() || value < 0.0 {
+            return Err(Error::Genetic(format!("Fitness {} cannot be used as a weight, it must be non-negative", value)));
+        }
+        Ok(if Self::MINIMIZED { 1.0 / (1.0 + value) } else { value })
+    }
+}
+
 pub trait FitnessFunction: {
     type Phenotype: Phenotype;
     type Fitness: Fitness;
@@ -19,6 +42,14 @@ pub trait Incubator {
     type Phenotype: Phenotype;
 
     fn grow(&self, genome: &Self::Genotype) -> Result<Self::Phenotype>;
+
+    /// Grow a batch of genomes, preserving their order.
+    fn grow_all(&self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Phenotype>> {
+        genomes
+            .iter()
+            .map(|genome| self.grow(genome))
+            .collect()
+    }
 }
 
 pub trait MutateOperator {
@@ -31,22 +62,43 @@ pub trait CrossoverOperator {
     type Genotype: Genotype;
 
     fn crossover(&mut self, genomes: &[&Self::Genotype]) -> Result<Vec<Self::Genotype>>;
+
+    /// A short name of the operator, recorded in the lineage of its offspring. Defaults to the
+    /// type name without module path and generic parameters.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
 }
 
-pub trait SelectOperator {
-    fn select<'a, G, F>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
+pub trait SelectOperator<F: Fitness> {
+    /// Groups of parents, one per crossover. Every group must be ordered best first, as
+    /// operators like [`HeuristicCrossover`](crate::crossover::HeuristicCrossover) rely on it.
+    fn select<'a, G>(&mut self, population: &'a SortedPopulation<G, F>) -> Result<Vec<Vec<&'a G>>>
         where
             G: Genotype,
-            F: Fitness,
     ;
 }
 
-pub trait ReinsertOperator {
-    fn reinsert<G, F>(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>>
-        where 
-            G: Genotype,
-            F: Fitness
-    ;
+pub trait ReinsertOperator<G: Genotype, F: Fitness> {
+    fn reinsert(&mut self, population: SortedPopulation<G, F>) -> Result<UnsortedPopulation<G, F>>;
+
+    /// Like [`reinsert`](Self::reinsert), knowing the parents of the children: `parents` maps
+    /// the id of every child to the ids of its parents. Called by
+    /// [`GeneticAlgorithm::advance`](crate::ga::GeneticAlgorithm::advance). The default
+    /// ignores the parents.
+    fn reinsert_with_parents(&mut self, population: SortedPopulation<G, F>, _parents: &HashMap<u64, Vec<u64>>) -> Result<UnsortedPopulation<G, F>> {
+        self.reinsert(population)
+    }
+}
+
+/// Chooses the member of a sorted population that an evaluated offspring replaces,
+/// in steady-state mode.
+pub trait ReplaceOperator<G: Genotype, F: Fitness> {
+    /// The index of the individual `child` replaces, or `None` to discard `child`.
+    /// `parents` are the indices in `population` of the parents of `child` that are still members.
+    fn replace(&mut self, population: &SortedPopulation<G, F>, child: &Individual<G, F>, parents: &[usize]) -> Result<Option<usize>>;
 }
 
 /// An Incubator that returns a Phenotype that is the same as Genotype